@@ -2,12 +2,24 @@ base_file=./src/bin/$1/main.rs
 submit_file=./src/bin/submit.rs
 rm -f $submit_file
 
-# mod xxx; 以外を抜き出して提出ファイルに追加
-cat $base_file | grep -v "^mod*" > $submit_file
+# mod xxx; と #[path = "..."] 以外を抜き出して提出ファイルに追加
+cat $base_file | grep -v "^mod*" | grep -v "^#\[path" > $submit_file
+
 # mod xxx; からxxxを抽出
-items=(`cat $base_file | grep "^mod*" | sed -E "s/mod (.*);(.*)/\1/"`)
+# 直前に #[path = "..."] があれば、そのファイルを使う (main.rs からの相対パス)
+path=""
+while read -r line; do
+    if [[ $line =~ ^#\[path\ =\ \"(.*)\"\] ]]; then
+        path=${BASH_REMATCH[1]}
+        continue
+    fi
+    if ! [[ $line =~ ^mod\ ([a-z_0-9]*)\; ]]; then
+        continue
+    fi
+    item=${BASH_REMATCH[1]}
+    file=./src/bin/$1/${path:-$item.rs}
+    path=""
 
-for item in "${items[@]}" ; do
     echo -e "\n" >> $submit_file
 
     # オプション
@@ -18,8 +30,8 @@ for item in "${items[@]}" ; do
     fi
 
     echo "mod $item {" >> $submit_file
-    cat ./src/bin/$1/$item.rs >> $submit_file
+    cat $file >> $submit_file
     echo "}" >> $submit_file
-done
+done < $base_file
 
-rustfmt $submit_file
\ No newline at end of file
+rustfmt $submit_file
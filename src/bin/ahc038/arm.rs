@@ -8,7 +8,11 @@ use crate::{
     state::{to_direction, to_rotate_direction, Direction, MoveAction},
 };
 
-#[derive(Debug)]
+/// 腕の木構造
+/// 辺 idx はノード idx + 1 と parents[idx] を結ぶ (ノード0が根)
+/// 辺 0..not_finger_arm_num は関節で、親は 0..=idx のノード
+/// それ以降の辺は指で、親は 0..=not_finger_arm_num のノード (指先は葉)
+#[derive(Debug, Clone)]
 pub struct Arm {
     N: usize,
     pub start: Coord,
@@ -19,6 +23,14 @@ pub struct Arm {
     pub parents: Vec<usize>, // parentのnode番号(0-indexed)
 }
 
+/// 関節の回転を決めたときの、関節ノード 0..=not_finger_arm_num の配置
+#[derive(Debug, Clone)]
+pub struct JointPlacement {
+    pub positions: Vec<Coord>,                 // 根からの相対位置
+    pub rotates: Vec<usize>,                   // 子の辺に伝搬される累積回転数
+    pub actions: Vec<(MoveAction, Direction)>, // 関節の辺ごとの回転行動と回転後の向き
+}
+
 impl Arm {
     pub fn new(N: usize, V: usize, not_finger_arm_num: usize) -> Self {
         let finger_num = V - not_finger_arm_num - 1;
@@ -50,16 +62,24 @@ impl Arm {
         &self,
         arm_direction: &Vec<Direction>,
         opposite: bool,
-    ) -> Vec<(Coord, Vec<(MoveAction, Direction)>)> {
-        let finger_parent_depth = self.not_finger_arm_num;
-        // 現在の位置, 累積回転数, 深さ, 行動
-        let mut Q = vec![(Coord::new(0, 0), 0, 0, vec![])];
+    ) -> Vec<JointPlacement> {
+        let joint_num = self.not_finger_arm_num;
+        let init = JointPlacement {
+            positions: vec![Coord::new(0, 0)],
+            rotates: vec![0],
+            actions: vec![],
+        };
+        // 配置, 次に回転を決める関節の辺
+        let mut Q = vec![(init, 0)];
         let mut ret = vec![];
-        while let Some((pos, rotate, depth, actions)) = Q.pop() {
-            if depth == finger_parent_depth {
-                ret.push((pos, actions));
+        while let Some((placement, depth)) = Q.pop() {
+            if depth == joint_num {
+                ret.push(placement);
                 continue;
             }
+            let parent = self.parents[depth];
+            let pos = placement.positions[parent];
+            let rotate = placement.rotates[parent];
             let len = self.lengths[depth];
             let dir: Direction = arm_direction[depth];
             for i in 0..=3 {
@@ -68,26 +88,26 @@ impl Arm {
                     continue;
                 }
                 let next_dir: Direction = to_direction((dir as usize + rotate + i) % 4); // 腕の累積回転数を加える必要がある
-                let mut next_actions = actions.clone();
-                next_actions.push((to_rotate_direction(i), next_dir)); // 回転行動を追加
-                let next_rotate = (rotate + i) % 4; // 伝搬させる累積回転数
                 let delta = DIJ4[next_dir as usize] * Coord::new(len, len);
-                let next = pos + delta;
-                Q.push((next, next_rotate, depth + 1, next_actions));
+                let mut next = placement.clone();
+                next.positions.push(pos + delta);
+                next.rotates.push((rotate + i) % 4); // 伝搬させる累積回転数
+                next.actions.push((to_rotate_direction(i), next_dir)); // 回転行動を追加
+                Q.push((next, depth + 1));
             }
         }
         ret
     }
     pub fn can_reach(&self, opposite: bool) -> Vec<Vec<usize>> {
         let arm_direction = vec![Direction::Right; self.lengths.len()];
-        let finger_parent_relative_positions =
-            self.finger_parent_relative_position(&arm_direction, opposite);
+        let placements = self.finger_parent_relative_position(&arm_direction, opposite);
         let mut can_reach = vec![vec![0; self.N]; self.N];
-        for (pos, action) in finger_parent_relative_positions.iter() {
-            let parent_pos = self.start + *pos;
-            // 指がついた腕に伝搬される累積回転数を求める
-            let rotate = action.iter().fold(0, |sum, &x| (sum + x.0 as usize) % 4);
+        for placement in placements.iter() {
             for idx in self.fingers.iter() {
+                let parent = self.parents[*idx];
+                let parent_pos = self.start + placement.positions[parent];
+                // 指がついた腕に伝搬される累積回転数
+                let rotate = placement.rotates[parent];
                 let len = self.lengths[*idx];
                 let dir: Direction = to_direction((arm_direction[*idx] as usize + rotate) % 4);
 
@@ -135,6 +155,62 @@ impl Arm {
         }
         range_score + comprehensiveness * base / self.N / self.N
     }
+    /// 関節の親は手前の関節、指の親は関節のノードで、各関節は子を持つ
+    pub fn is_valid_topology(&self) -> bool {
+        let k = self.not_finger_arm_num;
+        let mut has_child = vec![false; k + 1];
+        for (idx, &p) in self.parents.iter().enumerate() {
+            if (idx < k && p > idx) || (idx >= k && p > k) {
+                return false;
+            }
+            has_child[p] = true;
+        }
+        has_child.iter().all(|&x| x)
+    }
+    /// 長さ、指の付け替え、関節の付け替えのいずれかをランダムに変更する
+    /// 戻り値は revert_change に渡す (変更の種類, 辺, 変更前の値)
+    pub fn random_change(&mut self, rng: &mut Pcg64Mcg) -> (usize, usize, usize) {
+        let k = self.not_finger_arm_num;
+        loop {
+            let t = rng.gen_range(0..3);
+            if t == 0 {
+                let arm_idx = rng.gen_range(0..self.lengths.len());
+                let before_length = self.lengths[arm_idx];
+                if rng.gen_bool(0.5) {
+                    self.lengths[arm_idx] =
+                        (before_length + rng.gen_range(1..self.N)).min(self.N - 1);
+                } else {
+                    let mut length = before_length.wrapping_sub(rng.gen_range(1..self.N));
+                    if length >= self.N || length == 0 {
+                        length = 1;
+                    }
+                    self.lengths[arm_idx] = length;
+                }
+                return (t, arm_idx, before_length);
+            }
+            let arm_idx = if t == 1 {
+                self.fingers[rng.gen_range(0..self.fingers.len())]
+            } else if k >= 2 {
+                rng.gen_range(1..k)
+            } else {
+                continue;
+            };
+            let before_parent = self.parents[arm_idx];
+            self.parents[arm_idx] = rng.gen_range(0..=arm_idx.min(k));
+            if self.parents[arm_idx] != before_parent && self.is_valid_topology() {
+                return (t, arm_idx, before_parent);
+            }
+            self.parents[arm_idx] = before_parent;
+        }
+    }
+    pub fn revert_change(&mut self, change: (usize, usize, usize)) {
+        let (t, arm_idx, before) = change;
+        if t == 0 {
+            self.lengths[arm_idx] = before;
+        } else {
+            self.parents[arm_idx] = before;
+        }
+    }
     pub fn climbing(&mut self, time_limit: f64, rng: &mut Pcg64Mcg) -> usize {
         let center = Coord::new(self.N / 2, self.N / 2);
         let mut base = 0;
@@ -147,22 +223,12 @@ impl Arm {
         eprintln!("Initial Arm Score = {}", best_score);
 
         while get_time() < time_limit {
-            let arm_idx = rng.gen_range(0..self.lengths.len());
-            let before_length = self.lengths[arm_idx];
-            if rng.gen_bool(0.5) {
-                self.lengths[arm_idx] = (before_length + rng.gen_range(1..self.N)).min(self.N - 1);
-            } else {
-                let mut length = before_length - rng.gen_range(1..self.N);
-                if length >= self.N || length == 0 {
-                    length = 1;
-                }
-                self.lengths[arm_idx] = length;
-            }
+            let change = self.random_change(rng);
             let score = self.calc_score(&self.can_reach(opposite), base);
             if score > best_score {
                 best_score = score;
             } else {
-                self.lengths[arm_idx] = before_length;
+                self.revert_change(change);
             }
         }
 
@@ -3,23 +3,28 @@ use std::cmp::Reverse;
 use rustc_hash::FxHashSet;
 
 use crate::{
+    common::get_time,
     input::Input,
     state::{Op, State},
 };
 
+// 時間に合わせて幅を狭めるときの最小の幅
+const MIN_WIDTH: usize = 50;
+const TAIL_RATIO: f64 = 0.3;
+
 #[derive(Debug, Clone)]
 pub struct Node {
     pub track_id: usize,
     pub state: State,
 }
 impl Node {
-    fn new_node(&self, cand: &Cand) -> Node {
+    fn new_node(&self, input: &Input, cand: &Cand) -> Node {
         let mut ret = self.clone();
-        ret.apply(cand);
+        ret.apply(input, cand);
         ret
     }
-    fn apply(&mut self, cand: &Cand) {
-        self.state.apply(cand.eval_score, cand.hash, &cand.op);
+    fn apply(&mut self, input: &Input, cand: &Cand) {
+        self.state.apply(input, cand.score, cand.hash, &cand.op);
     }
 }
 
@@ -27,13 +32,14 @@ impl Node {
 struct Cand {
     op: Op,
     parent: usize,
+    score: usize,
     eval_score: usize,
     hash: usize,
     is_done: bool,
 }
 impl Cand {
     fn raw_score(&self, _input: &Input) -> usize {
-        self.eval_score
+        self.score
     }
 }
 
@@ -55,11 +61,12 @@ impl BeamSearch {
     fn append_cands(&self, input: &Input, cands: &mut Vec<Cand>, _rng: &mut rand_pcg::Pcg64Mcg) {
         for parent_idx in 0..self.nodes.len() {
             let parent_node = &self.nodes[parent_idx];
-            for (delta_score, hash, op, is_done) in parent_node.state.cand(input) {
+            for (delta_score, eval_score, hash, op, is_done) in parent_node.state.cand(input) {
                 let cand = Cand {
                     op,
                     parent: parent_idx,
-                    eval_score: parent_node.state.score + delta_score,
+                    score: parent_node.state.score + delta_score,
+                    eval_score,
                     hash,
                     is_done,
                 };
@@ -68,11 +75,11 @@ impl BeamSearch {
         }
     }
 
-    fn update<I: Iterator<Item = Cand>>(&mut self, cands: I) {
+    fn update<I: Iterator<Item = Cand>>(&mut self, input: &Input, cands: I) {
         self.next_nodes.clear();
         for cand in cands {
             let parent_node = &self.nodes[cand.parent];
-            let mut new_node = parent_node.new_node(&cand);
+            let mut new_node = parent_node.new_node(input, &cand);
             self.track.push((parent_node.track_id, cand.op));
             new_node.track_id = self.track.len() - 1;
             self.next_nodes.push(new_node);
@@ -98,9 +105,16 @@ impl BeamSearch {
         input: &Input,
         _rng: &mut rand_pcg::Pcg64Mcg,
         is_ascending: bool,
+        time_limit: f64,
+        stop_at_time_limit: bool,
     ) -> Vec<Op> {
         let mut cands = Vec::<Cand>::new();
         let mut set = FxHashSet::default();
+        // 直前の層を作り始めた時刻
+        let mut layer_start = get_time();
+        // 進捗が止まっている間は幅を狭めない
+        let mut cur_width = width;
+        let mut best_score = 0;
         for t in 0..depth {
             if t != 0 {
                 if is_ascending {
@@ -112,12 +126,28 @@ impl BeamSearch {
                 if best_cand.is_done {
                     break;
                 }
+                // 途中の結果で良い場合は、time_limit を過ぎたら打ち切る
+                if stop_at_time_limit && get_time() > time_limit {
+                    break;
+                }
+                if best_cand.score > best_score {
+                    best_score = best_cand.score;
+                    cur_width = adjust_width(
+                        width,
+                        t,
+                        best_cand.score as f64 / input.necessary_score as f64,
+                        (get_time() - layer_start) / self.nodes.len() as f64,
+                        time_limit - get_time(),
+                    );
+                }
+                layer_start = get_time();
                 set.clear();
                 self.update(
+                    input,
                     cands
                         .iter()
                         .filter(|cand| set.insert(cand.hash))
-                        .take(width)
+                        .take(cur_width)
                         .cloned(),
                 );
             }
@@ -135,3 +165,33 @@ impl BeamSearch {
         ret
     }
 }
+
+// 進捗から残りの層の数を見積もり、time_limit までに終わるように幅を決める
+// progress: 必要なスコアに対する達成割合, node_time: 1ノードの展開にかかる時間
+fn adjust_width(width: usize, t: usize, progress: f64, node_time: f64, rest_time: f64) -> usize {
+    if progress <= 0.0 {
+        return width;
+    }
+    // 最後の数個を運ぶのに時間がかかるので、残りの層の数は少なくとも TAIL_RATIO * t とする
+    let rest_layers = (t as f64 * (1.0 - progress) / progress)
+        .max(t as f64 * TAIL_RATIO)
+        .max(1.0);
+    let budget = rest_time.max(0.0) / rest_layers / node_time;
+    (budget as usize).max(MIN_WIDTH).min(width)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{adjust_width, MIN_WIDTH};
+
+    #[test]
+    fn test_adjust_width() {
+        // 進捗がないうちは幅を変えない
+        assert_eq!(adjust_width(250, 3, 0.0, 1e-3, 0.1), 250);
+        // 10層で半分進んだので残り10層、1層あたり 0.1 秒で 1ノード 1ms なら幅 100
+        assert_eq!(adjust_width(250, 10, 0.5, 1e-3, 1.0), 100);
+        // 時間に余裕があれば元の幅、時間切れなら最小の幅
+        assert_eq!(adjust_width(250, 10, 0.5, 1e-6, 1.0), 250);
+        assert_eq!(adjust_width(250, 10, 0.5, 1e-3, -1.0), MIN_WIDTH);
+    }
+}
@@ -0,0 +1,60 @@
+use crate::coord::Coord;
+
+// N <= 30 なので N * N <= 900 bit
+const WORDS: usize = 15;
+
+/// たこ焼きの有無を N * N bit で持つ盤面
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Bitboard {
+    N: usize,
+    words: [u64; WORDS],
+}
+
+impl Bitboard {
+    pub fn new(N: usize) -> Self {
+        assert!(N * N <= WORDS * 64);
+        Self {
+            N,
+            words: [0; WORDS],
+        }
+    }
+    fn index(&self, pos: Coord) -> (usize, u64) {
+        let k = pos.i * self.N + pos.j;
+        (k >> 6, 1 << (k & 63))
+    }
+    pub fn contains(&self, pos: Coord) -> bool {
+        let (w, b) = self.index(pos);
+        self.words[w] & b != 0
+    }
+    pub fn insert(&mut self, pos: Coord) {
+        let (w, b) = self.index(pos);
+        self.words[w] |= b;
+    }
+    pub fn remove(&mut self, pos: Coord) {
+        let (w, b) = self.index(pos);
+        self.words[w] &= !b;
+    }
+    pub fn count(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Bitboard;
+    use crate::coord::Coord;
+
+    #[test]
+    fn test_bitboard() {
+        let N = 30;
+        let mut board = Bitboard::new(N);
+        board.insert(Coord::new(0, 0));
+        board.insert(Coord::new(29, 29));
+        board.insert(Coord::new(2, 4));
+        assert!(board.contains(Coord::new(29, 29)));
+        assert!(!board.contains(Coord::new(4, 2)));
+        board.remove(Coord::new(0, 0));
+        assert!(!board.contains(Coord::new(0, 0)));
+        assert_eq!(board.count(), 2);
+    }
+}
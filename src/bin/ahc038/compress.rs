@@ -0,0 +1,336 @@
+use crate::{
+    input::Input,
+    sim::{verify, Simulator},
+};
+
+// 掴む・離すを行うターン
+#[derive(Debug, Clone)]
+struct Key {
+    r: (i32, i32),                    // 移動後の根の座標
+    req: Vec<(usize, usize)>,         // 掴む・離す葉までの経路上の (辺, 向き)
+    events: Vec<(usize, (i32, i32))>, // 掴む・離すを行った (頂点, 座標)
+}
+
+// 別の姿勢を列挙する経路上の辺の数の上限
+const MAX_FREE_JOINTS: usize = 5;
+
+/// 掴む・離すを行うターンだけを残し、根の移動と辺の回転を最短のターン数になるように配置し直す
+/// 盤面が変わるのは掴む・離すときだけなので、そのときの根の座標と、掴む・離す葉までの経路上の辺の向きが同じなら結果は変わらない
+/// 経路上にない辺は自由に回せるので、後で必要な向きへ前もって回しておく
+/// さらに、同じマスに葉を置く別の根の座標と辺の向きの中から、移動が短くなるものを選び直す
+pub fn compress(input: &Input, turns: &Vec<Vec<char>>) -> Vec<Vec<char>> {
+    if verify(input, turns).is_err() {
+        return turns.clone();
+    }
+    compress_from(&Simulator::new(input), turns)
+}
+
+fn compress_from(init: &Simulator, turns: &Vec<Vec<char>>) -> Vec<Vec<char>> {
+    let V = init.V;
+    let mut sim = init.clone();
+    let mut keys: Vec<Key> = vec![];
+    for s in turns.iter() {
+        let events = sim.apply(s).unwrap();
+        if events.is_empty() {
+            continue;
+        }
+        let mut req = vec![];
+        for &(v, _) in events.iter() {
+            let mut u = v;
+            while u > 0 {
+                if !req.iter().any(|&(w, _)| w == u) {
+                    req.push((u, sim.dirs[u]));
+                }
+                u = sim.pL[u - 1].0;
+            }
+        }
+        // 根の座標と共通の辺の向きが同じで、頂点もマスも重ならない直前の掴む・離すとは同じターンにまとめる
+        if let Some(last) = keys.last_mut() {
+            if last.r == sim.r
+                && req
+                    .iter()
+                    .all(|&(u, d)| last.req.iter().all(|&(w, e)| u != w || d == e))
+                && events
+                    .iter()
+                    .all(|e| last.events.iter().all(|f| e.0 != f.0 && e.1 != f.1))
+            {
+                for &(u, d) in req.iter() {
+                    if !last.req.iter().any(|&(w, _)| w == u) {
+                        last.req.push((u, d));
+                    }
+                }
+                last.events.extend(events);
+                continue;
+            }
+        }
+        keys.push(Key {
+            r: sim.r,
+            req,
+            events,
+        });
+    }
+
+    // 各掴む・離すで、同じマスに葉を置く別の姿勢を列挙し、前後の姿勢の間に必要なターン数の和が最小になるものを選ぶ
+    let cands = keys.iter().map(|key| poses(init, key)).collect::<Vec<_>>();
+    let init_req = (1..V).map(|u| (u, init.dirs[u])).collect::<Vec<_>>();
+    // dp[i][c] = (i番目の掴む・離すを姿勢 c で行うまでのターン数, 直前の姿勢)
+    let mut dp: Vec<Vec<(usize, usize)>> = vec![];
+    for i in 0..keys.len() {
+        let layer = cands[i]
+            .iter()
+            .map(|key| {
+                if i == 0 {
+                    return (gap(init.r, &init_req, key), !0);
+                }
+                (0..cands[i - 1].len())
+                    .map(|j| {
+                        let prev = &cands[i - 1][j];
+                        (dp[i - 1][j].0 + gap(prev.r, &prev.req, key), j)
+                    })
+                    .min()
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+        dp.push(layer);
+    }
+    let mut chosen = vec![];
+    if let Some(last) = dp.last() {
+        let mut j = (0..last.len()).min_by_key(|&j| last[j]).unwrap();
+        for i in (0..keys.len()).rev() {
+            chosen.push(cands[i][j].clone());
+            j = dp[i][j].1;
+        }
+        chosen.reverse();
+    }
+
+    // 選んだ姿勢でのターン数は見積もりなので、元の姿勢と比べて短い方を使う
+    let ret = schedule(init, &chosen);
+    let original = schedule(init, &keys);
+    if ret.len() < original.len() {
+        ret
+    } else {
+        original
+    }
+}
+
+// 掴む・離す葉を同じマスに置く、根の座標と経路上の辺の向きを全て列挙する
+fn poses(init: &Simulator, key: &Key) -> Vec<Key> {
+    let joints = key.req.iter().map(|&(u, _)| u).collect::<Vec<_>>();
+    if joints.len() > MAX_FREE_JOINTS {
+        return vec![key.clone()];
+    }
+    let mut sim = init.clone();
+    sim.r = (0, 0);
+    let mut ret = vec![];
+    for mask in 0..1usize << (2 * joints.len()) {
+        for (k, &u) in joints.iter().enumerate() {
+            sim.dirs[u] = mask >> (2 * k) & 3;
+        }
+        let roots = key
+            .events
+            .iter()
+            .map(|&(v, (x, y))| {
+                let (dx, dy) = sim.get(v);
+                (x - dx, y - dy)
+            })
+            .collect::<Vec<_>>();
+        let r = roots[0];
+        if roots.iter().any(|&q| q != r)
+            || r.0 < 0
+            || r.1 < 0
+            || r.0 >= init.N as i32
+            || r.1 >= init.N as i32
+        {
+            continue;
+        }
+        ret.push(Key {
+            r,
+            req: joints.iter().map(|&u| (u, sim.dirs[u])).collect(),
+            events: key.events.clone(),
+        });
+    }
+    ret
+}
+
+// 根の座標 r と辺の向き req の姿勢から key の姿勢にするまでのターン数
+// req にない辺は前もって回しておけるものとみなす
+fn gap(r: (i32, i32), req: &Vec<(usize, usize)>, key: &Key) -> usize {
+    let mut t = 1.max(((key.r.0 - r.0).abs() + (key.r.1 - r.1).abs()) as usize);
+    for &(u, d) in key.req.iter() {
+        if let Some(&(_, e)) = req.iter().find(|&&(w, _)| w == u) {
+            t = t.max(rotate_num(e, d));
+        }
+    }
+    t
+}
+
+// 各掴む・離すを、前の掴む・離すからの根の移動と、各辺が前に向きを決められた時刻からの回転が間に合う最も早いターンに置く
+fn schedule(init: &Simulator, keys: &Vec<Key>) -> Vec<Vec<char>> {
+    let V = init.V;
+    let mut times = vec![];
+    let mut prev_t = 0;
+    let mut prev_r = init.r;
+    let mut last = (0..V).map(|i| (0, init.dirs[i])).collect::<Vec<_>>();
+    for key in keys.iter() {
+        let mut t = prev_t + 1;
+        t = t.max(prev_t + ((key.r.0 - prev_r.0).abs() + (key.r.1 - prev_r.1).abs()) as usize);
+        for &(u, d) in key.req.iter() {
+            t = t.max(last[u].0 + rotate_num(last[u].1, d));
+        }
+        for &(u, d) in key.req.iter() {
+            last[u] = (t, d);
+        }
+        times.push(t);
+        prev_t = t;
+        prev_r = key.r;
+    }
+
+    let mut ret = vec![vec!['.'; 2 * V]; prev_t];
+    let mut r = init.r;
+    let mut prev_t = 0;
+    let mut dirs = init.dirs.clone();
+    let mut dir_t = vec![0; V];
+    for (key, &t) in keys.iter().zip(times.iter()) {
+        // 根は前の掴む・離すの直後から動かす
+        for row in ret[prev_t..t].iter_mut() {
+            row[0] = if r.0 < key.r.0 {
+                r.0 += 1;
+                'D'
+            } else if r.0 > key.r.0 {
+                r.0 -= 1;
+                'U'
+            } else if r.1 < key.r.1 {
+                r.1 += 1;
+                'R'
+            } else if r.1 > key.r.1 {
+                r.1 -= 1;
+                'L'
+            } else {
+                '.'
+            };
+        }
+        // 辺は前に向きを決められた直後から回す
+        for &(u, d) in key.req.iter() {
+            for row in ret[dir_t[u]..t].iter_mut() {
+                if (d + 4 - dirs[u]) % 4 == 3 {
+                    dirs[u] = (dirs[u] + 3) % 4;
+                    row[u] = 'L';
+                } else if dirs[u] != d {
+                    dirs[u] = (dirs[u] + 1) % 4;
+                    row[u] = 'R';
+                }
+            }
+            dir_t[u] = t;
+        }
+        for &(v, _) in key.events.iter() {
+            ret[t - 1][V + v] = 'P';
+        }
+        prev_t = t;
+    }
+    ret
+}
+
+// 向き from から to まで回転させるのに必要なターン数
+fn rotate_num(from: usize, to: usize) -> usize {
+    match (to + 4 - from) % 4 {
+        0 => 0,
+        2 => 2,
+        _ => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::compress_from;
+    use crate::sim::Simulator;
+
+    #[test]
+    fn test_compress() {
+        // 根 (0, 0) に長さ1の辺で葉がつながった腕で、(1, 1) のたこ焼きを (2, 1) に運ぶ
+        let mut board = vec![vec![false; 5]; 5];
+        board[1][1] = true;
+        let init = Simulator {
+            N: 5,
+            V: 2,
+            r: (0, 0),
+            pL: vec![(0, 1)],
+            is_leaf: vec![false, true],
+            dirs: vec![0; 2],
+            has: vec![false; 2],
+            board,
+        };
+        let turns = ["R...", "....", ".R..", "...P", "D...", "...P", "L..."]
+            .iter()
+            .map(|s| s.chars().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let compressed = compress_from(&init, &turns);
+        let expected = ["RR.P", "D..P"]
+            .iter()
+            .map(|s| s.chars().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(compressed, expected);
+
+        let run = |turns: &Vec<Vec<char>>| {
+            let mut sim = init.clone();
+            for s in turns.iter() {
+                sim.apply(s).unwrap();
+            }
+            sim.board
+        };
+        assert_eq!(run(&compressed), run(&turns));
+    }
+
+    #[test]
+    fn test_compress_merge() {
+        // 180度の回転は2ターンかかるが、別の葉が掴む間に前もって回しておける
+        let mut board = vec![vec![false; 5]; 5];
+        board[2][3] = true;
+        board[2][1] = true;
+        let init = Simulator {
+            N: 5,
+            V: 3,
+            r: (2, 2),
+            pL: vec![(0, 1), (0, 1)],
+            is_leaf: vec![false, true, true],
+            dirs: vec![0; 3],
+            has: vec![false; 3],
+            board,
+        };
+        let turns = [
+            ".RR...", ".LL...", ".RR...", ".LL...", "....P.", "..R...", "......", "..R...",
+            ".....P",
+        ]
+        .iter()
+        .map(|s| s.chars().collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+        let compressed = compress_from(&init, &turns);
+        let expected = ["..R...", "..R.PP"]
+            .iter()
+            .map(|s| s.chars().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(compressed, expected);
+    }
+
+    #[test]
+    fn test_compress_pose() {
+        // 根を (1, 0) まで動かして (1, 1) を掴む代わりに、根を1回動かして辺を上に向ける
+        let mut board = vec![vec![false; 5]; 5];
+        board[1][1] = true;
+        let init = Simulator {
+            N: 5,
+            V: 2,
+            r: (2, 2),
+            pL: vec![(0, 1)],
+            is_leaf: vec![false, true],
+            dirs: vec![0; 2],
+            has: vec![false; 2],
+            board,
+        };
+        let turns = ["U...", "L...", "L..P"]
+            .iter()
+            .map(|s| s.chars().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let compressed = compress_from(&init, &turns);
+        assert_eq!(compressed, vec!["LL.P".chars().collect::<Vec<_>>()]);
+    }
+}
@@ -0,0 +1,122 @@
+use rand_pcg::Pcg64Mcg;
+
+use crate::{
+    arm::Arm,
+    beam::{BeamSearch, Node},
+    common::get_time,
+    input::Input,
+    state::{FingerAction, State},
+};
+
+const TIME_LIMIT: f64 = 1.0;
+// 到達範囲のヒューリスティックで初期形状を作る時間
+const INIT_TIME: f64 = 0.15;
+const EVAL_WIDTH: usize = 8;
+const EVAL_DEPTH: usize = 60;
+const MIN_EVAL_DEPTH: usize = 10;
+// 1回の見積もりにかける時間の目安
+const EVAL_TIME: f64 = 0.1;
+
+/// ターン数の見積もり
+#[derive(Debug, Clone, Copy)]
+pub struct Estimate {
+    pub turn: f64,
+    pub layers: usize,  // 回したビームサーチの層の数
+    pub finished: bool, // 全て運び終えたか
+}
+
+/// input.arm で幅の小さいビームサーチを depth 層まで回し、総ターン数を見積もる
+/// 運び終えなかった場合は、それまでに得たスコアの割合から線形に外挿する
+/// time_limit を過ぎたらその層で打ち切る
+pub fn estimate_turn(input: &Input, rng: &mut Pcg64Mcg, depth: usize, time_limit: f64) -> Estimate {
+    let init_node = Node {
+        track_id: !0,
+        state: State::new(input),
+    };
+    let mut beam = BeamSearch::new(init_node);
+    let ops = beam.solve(EVAL_WIDTH, depth, input, rng, false, time_limit, true);
+    let score: usize = ops
+        .iter()
+        .flat_map(|op| op.finger_actions.iter())
+        .map(|&(action, _, _)| match action {
+            FingerAction::Grab => input.grab_score,
+            FingerAction::Release => input.release_score,
+            _ => 0,
+        })
+        .sum();
+    let finished = score >= input.necessary_score;
+    let turn = if finished {
+        ops.len() as f64
+    } else {
+        ops.len() as f64 * input.necessary_score as f64 / score.max(1) as f64
+    };
+    Estimate {
+        turn,
+        layers: ops.len(),
+        finished,
+    }
+}
+
+/// 到達範囲のヒューリスティックで選んだ腕から始め、形状と長さを山登りする
+/// 見積もりは最初の腕で時間内に回せた深さにそろえ、同じ深さで見積もりが真に小さくなるときだけ腕を置き換える
+pub fn make_arm(input: &mut Input) {
+    let mut rng = Pcg64Mcg::new(100);
+    let (base, other) = base_arms(input.N, input.V, &mut rng);
+    input.arm = base;
+    let eval_start = get_time();
+    let estimate = estimate_turn(input, &mut rng, EVAL_DEPTH, eval_start + EVAL_TIME);
+    let eval_depth = if estimate.finished {
+        EVAL_DEPTH
+    } else {
+        estimate.layers
+    };
+    eprintln!(
+        "Initial Estimated Turn = {:.1} (depth = {})",
+        estimate.turn, eval_depth
+    );
+    // 浅すぎる見積もりでは比べられないので、最初の腕を使う
+    if !estimate.finished && eval_depth < MIN_EVAL_DEPTH {
+        return;
+    }
+    // 時間切れで深さが足りない見積もりは採用しない
+    let estimate_at_depth = |input: &Input, rng: &mut Pcg64Mcg| {
+        let estimate = estimate_turn(input, rng, eval_depth, TIME_LIMIT);
+        if estimate.finished || estimate.layers == eval_depth {
+            Some(estimate.turn)
+        } else {
+            None
+        }
+    };
+    let mut best_turn = estimate.turn;
+
+    let base = std::mem::replace(&mut input.arm, other);
+    match estimate_at_depth(input, &mut rng) {
+        Some(turn) if turn < best_turn => best_turn = turn,
+        _ => input.arm = base,
+    }
+    let mut iter = 0;
+    while get_time() < TIME_LIMIT {
+        iter += 1;
+        let change = input.arm.random_change(&mut rng);
+        match estimate_at_depth(input, &mut rng) {
+            Some(turn) if turn < best_turn => best_turn = turn,
+            _ => input.arm.revert_change(change),
+        }
+    }
+    eprintln!("Final Estimated Turn = {:.1} (iter = {})", best_turn, iter);
+}
+
+/// 関節が 2本と 3本の腕をそれぞれ到達範囲で山登りし、2本の方のスコアを 1.5 倍して良い方を先に返す
+fn base_arms(N: usize, V: usize, rng: &mut Pcg64Mcg) -> (Arm, Arm) {
+    let start = get_time();
+    let mut arm2 = Arm::new(N, V, 2);
+    let score2 = arm2.climbing(start + INIT_TIME, rng) * 3 / 2;
+    let start = get_time();
+    let mut arm3 = Arm::new(N, V, 3);
+    let score3 = arm3.climbing(start + INIT_TIME, rng);
+    if score2 > score3 {
+        (arm2, arm3)
+    } else {
+        (arm3, arm2)
+    }
+}
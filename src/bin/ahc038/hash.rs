@@ -3,7 +3,7 @@ use std::collections::HashSet;
 use rand::Rng;
 use rand_pcg::Pcg64Mcg;
 
-use crate::{coord::Coord, state::Direction};
+use crate::{bitboard::Bitboard, coord::Coord, state::Direction};
 
 #[derive(Debug)]
 pub struct CalcHash {
@@ -105,9 +105,33 @@ impl CalcHash {
         root_pos2: Coord,
         arm_directions: &Vec<(Direction, Direction)>,
     ) -> usize {
-        hash ^= self.calc_field_status(hash, field_change_coords);
-        hash ^= self.calc_root_position(hash, root_pos1, root_pos2);
-        hash ^= self.calc_arm_direction(hash, arm_directions);
+        hash = self.calc_field_status(hash, field_change_coords);
+        hash = self.calc_root_position(hash, root_pos1, root_pos2);
+        hash = self.calc_arm_direction(hash, arm_directions);
         hash
     }
+    /// 差分更新の検証用に、盤面・根・腕の向きから求め直す
+    pub fn calc_from_scratch(
+        &self,
+        N: usize,
+        field: &Bitboard,
+        root: Coord,
+        arm_direction: &Vec<Direction>,
+    ) -> usize {
+        let mut ret = 0;
+        for i in 0..N {
+            for j in 0..N {
+                if field.contains(Coord::new(i, j)) {
+                    ret ^= self.field_status_hash_map[i][j].1;
+                } else {
+                    ret ^= self.field_status_hash_map[i][j].0;
+                }
+            }
+        }
+        ret ^= self.root_position_hash_map[root.i][root.j];
+        for (dir, mp) in arm_direction.iter().zip(self.arm_direction_hash_map.iter()) {
+            ret ^= mp[*dir as usize];
+        }
+        ret
+    }
 }
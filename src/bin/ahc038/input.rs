@@ -1,25 +1,10 @@
 use proconio::{input, marker::Chars};
-use rand_pcg::Pcg64Mcg;
 
-use crate::{arm::Arm, common::get_time, hash::CalcHash};
+use crate::{arm::Arm, coord::Coord, design::make_arm, hash::CalcHash, plan::Plan};
 
 const GRAB_SCORE: usize = 1;
 const RELEASE_SCORE: usize = 2;
 
-pub fn make_arm(N: usize, V: usize) -> Arm {
-    let time_limit = 0.5;
-    let mut rng = Pcg64Mcg::new(100);
-    let start = get_time();
-    let mut arm2 = Arm::new(N, V, 2);
-    let score2 = arm2.climbing(start + time_limit, &mut rng) * 3 / 2;
-
-    let start = get_time();
-    let mut arm3 = Arm::new(N, V, 3);
-    let score3 = arm3.climbing(start + time_limit, &mut rng);
-    let arm = if score2 > score3 { arm2 } else { arm3 };
-    arm
-}
-
 pub fn read_input() -> Input {
     input! {
         N: usize,
@@ -38,19 +23,23 @@ pub fn read_input() -> Input {
         }
     }
     eprintln!("input: N = {}, M = {}, V = {}", N, M, V);
+    let plan = Plan::new(N, &S, &T, Coord::new(N / 2, N / 2));
 
-    Input {
+    let mut input = Input {
         N,
         M,
         V,
         S,
         T,
-        arm: make_arm(N, V),
+        arm: Arm::new(N, V, 2),
         calc_hash: CalcHash::new(N, V),
+        plan,
         grab_score: GRAB_SCORE,
         release_score: RELEASE_SCORE,
         necessary_score: M * (GRAB_SCORE + RELEASE_SCORE),
-    }
+    };
+    make_arm(&mut input);
+    input
 }
 
 pub fn parse_input(f: &str) -> Input {
@@ -70,19 +59,23 @@ pub fn parse_input(f: &str) -> Input {
         }
     }
     eprintln!("input: N = {}, M = {}, V = {}", N, M, V);
+    let plan = Plan::new(N, &S, &T, Coord::new(N / 2, N / 2));
 
-    Input {
+    let mut input = Input {
         N,
         M,
         V,
         S,
         T,
-        arm: make_arm(N, V),
+        arm: Arm::new(N, V, 2),
         calc_hash: CalcHash::new(N, V),
+        plan,
         grab_score: GRAB_SCORE,
         release_score: RELEASE_SCORE,
         necessary_score: M * (GRAB_SCORE + RELEASE_SCORE),
-    }
+    };
+    make_arm(&mut input);
+    input
 }
 
 #[derive(Debug)]
@@ -94,6 +87,7 @@ pub struct Input {
     pub T: Vec<Vec<char>>,
     pub arm: Arm,
     pub calc_hash: CalcHash,
+    pub plan: Plan,
     pub grab_score: usize,
     pub release_score: usize,
     pub necessary_score: usize,
@@ -3,20 +3,29 @@
 
 mod arm;
 mod beam;
+mod bitboard;
 mod common;
+mod compress;
 mod coord;
+mod design;
 mod hash;
 mod input;
+mod plan;
+mod sim;
 mod state;
 mod test;
 
 use beam::{BeamSearch, Node};
 use common::get_time;
+use compress::compress;
 use input::{read_input, Input};
 use rand_pcg::Pcg64Mcg;
+use sim::verify;
 use state::{move_action_to_direction, FingerAction, MoveAction, State};
 
 const DIRS: [char; 5] = ['R', 'D', 'L', 'U', '.'];
+// ビームサーチを終える時刻
+const TIME_LIMIT: f64 = 2.6;
 
 fn solve(input: &Input) {
     let mut rng = Pcg64Mcg::new(0);
@@ -26,7 +35,7 @@ fn solve(input: &Input) {
         state: init_state,
     };
     let mut beam = BeamSearch::new(init_node);
-    let mut ops = beam.solve(250, 500, &input, &mut rng, false);
+    let mut ops = beam.solve(250, 500, &input, &mut rng, false, TIME_LIMIT, false);
 
     // MoveActionがOppositeの場合は、直前と現在の行動をLeftにして、逆方向を向く
     for i in 1..ops.len() {
@@ -40,29 +49,42 @@ fn solve(input: &Input) {
         }
     }
 
-    // 出力
-    let mut output = input.arm.output();
-
+    let mut turns = vec![];
     for op in ops.iter() {
-        let mut action_out = "".to_string();
+        let mut action_out = vec![];
         for &(action, _) in op.move_actions.iter() {
-            action_out += DIRS[move_action_to_direction(action) as usize]
-                .to_string()
-                .as_str();
+            action_out.push(DIRS[move_action_to_direction(action) as usize]);
         }
         // 根は何もしない
-        action_out += ".";
+        action_out.push('.');
         for &(action, _, _) in op.finger_actions.iter() {
             if action == FingerAction::Grab || action == FingerAction::Release {
-                action_out += "P";
+                action_out.push('P');
             } else {
-                action_out += ".";
+                action_out.push('.');
             }
         }
-        output += format!("{}\n", action_out).as_str();
+        turns.push(action_out);
+    }
+
+    // 掴む・離す姿勢を選び直し、操作を前倒ししてターンを詰める
+    // シミュレータで検証し、失敗した場合は元の操作列を出力する
+    let compressed = compress(input, &turns);
+    match verify(input, &compressed) {
+        Ok(()) => {
+            eprintln!("Compressed: {} -> {}", turns.len(), compressed.len());
+            turns = compressed;
+        }
+        Err(err) => eprintln!("Compress failed: {}", err),
+    }
+
+    // 出力
+    let mut output = input.arm.output();
+    for s in turns.iter() {
+        output += format!("{}\n", s.iter().collect::<String>()).as_str();
     }
     println!("{}", output);
-    eprintln!("Score = {}", ops.len());
+    eprintln!("Score = {}", turns.len());
 }
 
 fn main() {
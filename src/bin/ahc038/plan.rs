@@ -0,0 +1,283 @@
+use crate::coord::{calc_manhattan_dist, Coord};
+
+// 根の巡回で使うブロックの一辺
+const BLOCK: usize = 5;
+// 評価値の重み (単位は 2N で、根と目的地の距離の項より常に大きくなる)
+const SCORE_WEIGHT: usize = 16;
+const MATCH_WEIGHT: usize = 2;
+const ORDER_WEIGHT: usize = 1;
+
+/// 掴む位置 (S=1, T=0) と離す位置 (S=0, T=1) の割り当てと、根の巡回順
+#[derive(Debug, Clone)]
+pub struct Plan {
+    N: usize,
+    unit: usize,
+    pub matched: Vec<Vec<Coord>>,   // 掴む位置 -> 割り当てた離す位置
+    pub cluster: Vec<Vec<usize>>,   // マス -> ブロック番号
+    pub centers: Vec<Coord>,        // ブロックの中心
+    pub tour: Vec<usize>,           // 未完了のマスを含むブロックの巡回順
+    pub rank: Vec<usize>,           // ブロック番号 -> 巡回順の何番目か
+    pub remaining: Vec<[usize; 2]>, // ブロックごとの [掴む位置の数, 離す位置の数]
+}
+
+impl Plan {
+    pub fn new(N: usize, S: &Vec<Vec<char>>, T: &Vec<Vec<char>>, start: Coord) -> Self {
+        let mut sources = vec![];
+        let mut targets = vec![];
+        for i in 0..N {
+            for j in 0..N {
+                if S[i][j] == '1' && T[i][j] == '0' {
+                    sources.push(Coord::new(i, j));
+                } else if S[i][j] == '0' && T[i][j] == '1' {
+                    targets.push(Coord::new(i, j));
+                }
+            }
+        }
+
+        // マンハッタン距離の和が最小になる割り当て
+        let cost: Vec<Vec<i64>> = sources
+            .iter()
+            .map(|&s| {
+                targets
+                    .iter()
+                    .map(|&t| calc_manhattan_dist(s, t) as i64)
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        let mut matched = vec![vec![Coord::new(!0, !0); N]; N];
+        for (s, t) in sources.iter().zip(hungarian(&cost)) {
+            matched[s.i][s.j] = targets[t];
+        }
+
+        let width = (N + BLOCK - 1) / BLOCK;
+        let mut cluster = vec![vec![0; N]; N];
+        let mut centers = vec![];
+        for bi in 0..width {
+            for bj in 0..width {
+                let ci = (bi * BLOCK + BLOCK / 2).min(N - 1);
+                let cj = (bj * BLOCK + BLOCK / 2).min(N - 1);
+                centers.push(Coord::new(ci, cj));
+            }
+        }
+        for i in 0..N {
+            for j in 0..N {
+                cluster[i][j] = i / BLOCK * width + j / BLOCK;
+            }
+        }
+        let mut remaining = vec![[0; 2]; centers.len()];
+        for c in sources.iter() {
+            remaining[cluster[c.i][c.j]][0] += 1;
+        }
+        for c in targets.iter() {
+            remaining[cluster[c.i][c.j]][1] += 1;
+        }
+
+        let tour = make_tour(&centers, &remaining, start);
+        let mut rank = vec![!0; centers.len()];
+        for (r, &c) in tour.iter().enumerate() {
+            rank[c] = r;
+        }
+
+        Self {
+            N,
+            unit: 2 * N,
+            matched,
+            cluster,
+            centers,
+            tour,
+            rank,
+            remaining,
+        }
+    }
+    /// 巡回順で最初の、今の指で行動できる未完了のブロック
+    /// 空いている指があれば掴む位置、掴んでいる指があれば離す位置が残っているブロックを選ぶ
+    pub fn waypoint(
+        &self,
+        remaining: &Vec<[usize; 2]>,
+        can_grab: bool,
+        can_release: bool,
+    ) -> Option<usize> {
+        self.tour
+            .iter()
+            .cloned()
+            .find(|&c| (can_grab && remaining[c][0] > 0) || (can_release && remaining[c][1] > 0))
+    }
+    pub fn score_weight(&self) -> usize {
+        SCORE_WEIGHT * self.unit
+    }
+    /// 割り当て通りの位置に離したときの加点
+    pub fn match_bonus(&self, source: Coord, target: Coord) -> usize {
+        if source.in_map(self.N) && self.matched[source.i][source.j] == target {
+            MATCH_WEIGHT * self.unit
+        } else {
+            0
+        }
+    }
+    /// 巡回中のブロックで掴む・離すときの加点
+    pub fn order_bonus(&self, pos: Coord, waypoint: Option<usize>) -> usize {
+        if waypoint == Some(self.cluster[pos.i][pos.j]) {
+            ORDER_WEIGHT * self.unit
+        } else {
+            0
+        }
+    }
+    /// 根が巡回中のブロックに近いほど大きい
+    pub fn position_score(&self, root: Coord, waypoint: Option<usize>) -> usize {
+        match waypoint {
+            Some(c) => self.unit - calc_manhattan_dist(root, self.centers[c]).min(self.unit),
+            None => self.unit,
+        }
+    }
+}
+
+/// 最小コストの完全マッチング (行 -> 列)
+fn hungarian(cost: &Vec<Vec<i64>>) -> Vec<usize> {
+    let n = cost.len();
+    if n == 0 {
+        return vec![];
+    }
+    let m = cost[0].len();
+    let inf = i64::MAX / 4;
+    let mut u = vec![0; n + 1];
+    let mut v = vec![0; m + 1];
+    // p[j]: 列jに割り当てた行 (1-indexed)
+    let mut p = vec![0; m + 1];
+    let mut way = vec![0; m + 1];
+    for i in 1..=n {
+        p[0] = i;
+        let mut j0 = 0;
+        let mut minv = vec![inf; m + 1];
+        let mut used = vec![false; m + 1];
+        loop {
+            used[j0] = true;
+            let i0 = p[j0];
+            let mut delta = inf;
+            let mut j1 = 0;
+            for j in 1..=m {
+                if used[j] {
+                    continue;
+                }
+                let cur = cost[i0 - 1][j - 1] - u[i0] - v[j];
+                if cur < minv[j] {
+                    minv[j] = cur;
+                    way[j] = j0;
+                }
+                if minv[j] < delta {
+                    delta = minv[j];
+                    j1 = j;
+                }
+            }
+            for j in 0..=m {
+                if used[j] {
+                    u[p[j]] += delta;
+                    v[j] -= delta;
+                } else {
+                    minv[j] -= delta;
+                }
+            }
+            j0 = j1;
+            if p[j0] == 0 {
+                break;
+            }
+        }
+        loop {
+            let j1 = way[j0];
+            p[j0] = p[j1];
+            j0 = j1;
+            if j0 == 0 {
+                break;
+            }
+        }
+    }
+    let mut ret = vec![!0; n];
+    for j in 1..=m {
+        if p[j] != 0 {
+            ret[p[j] - 1] = j - 1;
+        }
+    }
+    ret
+}
+
+/// 開始位置から最近傍法で巡回順を作り、2-optで改善する
+fn make_tour(centers: &Vec<Coord>, remaining: &Vec<[usize; 2]>, start: Coord) -> Vec<usize> {
+    let mut rest = (0..centers.len())
+        .filter(|&c| remaining[c] != [0, 0])
+        .collect::<Vec<_>>();
+    let mut tour = vec![];
+    let mut pos = start;
+    while !rest.is_empty() {
+        let k = (0..rest.len())
+            .min_by_key(|&k| calc_manhattan_dist(pos, centers[rest[k]]))
+            .unwrap();
+        let c = rest.swap_remove(k);
+        tour.push(c);
+        pos = centers[c];
+    }
+
+    // 始点を開始位置に固定した開路
+    let dist = |a: Coord, b: Coord| calc_manhattan_dist(a, b);
+    let point = |tour: &Vec<usize>, k: usize| {
+        if k == 0 {
+            start
+        } else {
+            centers[tour[k - 1]]
+        }
+    };
+    let mut improved = true;
+    while improved {
+        improved = false;
+        for a in 0..tour.len() {
+            for b in a + 1..tour.len() {
+                // tour[a..=b] を反転
+                let before = dist(point(&tour, a), centers[tour[a]])
+                    + if b + 1 < tour.len() {
+                        dist(centers[tour[b]], centers[tour[b + 1]])
+                    } else {
+                        0
+                    };
+                let after = dist(point(&tour, a), centers[tour[b]])
+                    + if b + 1 < tour.len() {
+                        dist(centers[tour[a]], centers[tour[b + 1]])
+                    } else {
+                        0
+                    };
+                if after < before {
+                    tour[a..=b].reverse();
+                    improved = true;
+                }
+            }
+        }
+    }
+    tour
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{hungarian, Plan};
+    use crate::coord::Coord;
+
+    #[test]
+    fn test_hungarian() {
+        let cost = vec![vec![4, 1, 3], vec![2, 0, 5], vec![3, 2, 2]];
+        let ret = hungarian(&cost);
+        let total: i64 = ret.iter().enumerate().map(|(i, &j)| cost[i][j]).sum();
+        assert_eq!(total, 5);
+    }
+
+    #[test]
+    fn test_waypoint() {
+        // 左上のブロックには離す位置だけ、右下のブロックには掴む位置だけがある
+        let N = 10;
+        let mut S = vec![vec!['0'; N]; N];
+        let mut T = vec![vec!['0'; N]; N];
+        S[9][9] = '1';
+        T[0][0] = '1';
+        let plan = Plan::new(N, &S, &T, Coord::new(0, 0));
+        let target = plan.cluster[0][0];
+        let source = plan.cluster[9][9];
+        assert_eq!(plan.waypoint(&plan.remaining, true, true), Some(target));
+        // 何も掴んでいなければ、離す位置しかないブロックは飛ばす
+        assert_eq!(plan.waypoint(&plan.remaining, true, false), Some(source));
+        assert_eq!(plan.waypoint(&plan.remaining, false, true), Some(target));
+    }
+}
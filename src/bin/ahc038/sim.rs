@@ -0,0 +1,164 @@
+use crate::input::Input;
+
+// tools/ahc038 の State::apply を移植したもの
+// 出力の1行 (根の移動, 各辺の回転, 各頂点のP) を文字のまま適用する
+
+pub const DIJ: [(i32, i32); 4] = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Simulator {
+    pub N: usize,
+    pub V: usize,
+    pub r: (i32, i32),
+    pub pL: Vec<(usize, usize)>,
+    pub is_leaf: Vec<bool>,
+    pub dirs: Vec<usize>,
+    pub has: Vec<bool>,
+    pub board: Vec<Vec<bool>>,
+}
+
+impl Simulator {
+    pub fn new(input: &Input) -> Self {
+        let pL = input
+            .arm
+            .parents
+            .iter()
+            .cloned()
+            .zip(input.arm.lengths.iter().cloned())
+            .collect::<Vec<_>>();
+        let V = pL.len() + 1;
+        let mut is_leaf = vec![true; V];
+        for &(p, _) in pL.iter() {
+            is_leaf[p] = false;
+        }
+        Simulator {
+            N: input.N,
+            V,
+            r: (input.arm.start.i as i32, input.arm.start.j as i32),
+            pL,
+            is_leaf,
+            dirs: vec![0; V],
+            has: vec![false; V],
+            board: input
+                .S
+                .iter()
+                .map(|row| row.iter().map(|&c| c == '1').collect())
+                .collect(),
+        }
+    }
+    pub fn get(&self, mut u: usize) -> (i32, i32) {
+        let mut vs = vec![];
+        while u > 0 {
+            let (v, l) = self.pL[u - 1];
+            vs.push((self.dirs[u], l));
+            u = v;
+        }
+        let mut p = self.r;
+        let mut dir = 0;
+        for &(d, l) in vs.iter().rev() {
+            dir = (dir + d) % 4;
+            let (dx, dy) = DIJ[dir];
+            p.0 += l as i32 * dx;
+            p.1 += l as i32 * dy;
+        }
+        p
+    }
+    /// 1ターン分の操作を適用し、掴む・離すを行った (頂点, 座標) を返す
+    pub fn apply(&mut self, s: &[char]) -> Result<Vec<(usize, (i32, i32))>, String> {
+        match s[0] {
+            'U' => {
+                self.r.0 -= 1;
+                if self.r.0 < 0 {
+                    return Err(format!("The root coordinate is out of range."));
+                }
+            }
+            'D' => {
+                self.r.0 += 1;
+                if self.r.0 == self.N as i32 {
+                    return Err(format!("The root coordinate is out of range."));
+                }
+            }
+            'L' => {
+                self.r.1 -= 1;
+                if self.r.1 < 0 {
+                    return Err(format!("The root coordinate is out of range."));
+                }
+            }
+            'R' => {
+                self.r.1 += 1;
+                if self.r.1 == self.N as i32 {
+                    return Err(format!("The root coordinate is out of range."));
+                }
+            }
+            '.' => {}
+            _ => {
+                return Err(format!("Invalid operation: {}", s[0]));
+            }
+        }
+        for i in 1..self.V {
+            match s[i] {
+                'L' => {
+                    self.dirs[i] = (self.dirs[i] + 3) % 4;
+                }
+                'R' => {
+                    self.dirs[i] = (self.dirs[i] + 1) % 4;
+                }
+                '.' => {}
+                _ => {
+                    return Err(format!("Invalid operation: {}", s[i]));
+                }
+            }
+        }
+        let mut events = vec![];
+        for i in 0..self.V {
+            match s[self.V + i] {
+                'P' => {
+                    if !self.is_leaf[i] {
+                        return Err(format!("The vertex {} is not a leaf.", i));
+                    }
+                    let (x, y) = self.get(i);
+                    if x < 0 || y < 0 || x >= self.N as i32 || y >= self.N as i32 {
+                        return Err(format!("The leaf coordinate is out of range."));
+                    }
+                    if self.has[i] {
+                        if self.board[x as usize][y as usize] {
+                            return Err(format!(
+                                "You cannot put multiple takoyaki on the same square."
+                            ));
+                        }
+                        self.has[i] = false;
+                        self.board[x as usize][y as usize] = true;
+                    } else {
+                        if !self.board[x as usize][y as usize] {
+                            return Err(format!("({}, {}) does not contain takoyaki.", x, y));
+                        }
+                        self.has[i] = true;
+                        self.board[x as usize][y as usize] = false;
+                    }
+                    events.push((i, (x, y)));
+                }
+                '.' => {}
+                _ => {
+                    return Err(format!("Invalid operation: {}", s[self.V + i]));
+                }
+            }
+        }
+        Ok(events)
+    }
+}
+
+/// 全ターンを適用し、最終盤面が T と一致するか確かめる
+pub fn verify(input: &Input, turns: &Vec<Vec<char>>) -> Result<(), String> {
+    let mut sim = Simulator::new(input);
+    for (t, s) in turns.iter().enumerate() {
+        sim.apply(s).map_err(|err| format!("turn {}: {}", t, err))?;
+    }
+    for i in 0..input.N {
+        for j in 0..input.N {
+            if sim.board[i][j] != (input.T[i][j] == '1') {
+                return Err(format!("({}, {}) does not match the target.", i, j));
+            }
+        }
+    }
+    Ok(())
+}
@@ -1,11 +1,21 @@
-use rustc_hash::FxHashSet;
+use std::cmp::Reverse;
 
 use crate::{
-    arm::Arm,
+    bitboard::Bitboard,
     coord::{Coord, DIJ4, DIJ5},
     input::Input,
 };
 
+// 1つの配置で残す指の行動の組み合わせの数
+const ASSIGN_BUDGET: usize = 4;
+// 指の行動の組み合わせで、1本の指の選択肢の番号に使うビット数 (選択肢は5個以下)
+const CHOICE_BITS: usize = 3;
+// 根の移動ごとに残す、スコア0で関節を回転させる移動の数の目安
+const ZERO_CAND_NUM: usize = 3;
+// 根の移動ごとに残す、掴む・離す移動の数
+// 配置の数と指の行動の組み合わせの数の積で候補が増えないように、評価値の上位だけを残す
+const SCORE_CAND_NUM: usize = 6;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Direction {
     Right,
@@ -15,6 +25,11 @@ pub enum Direction {
     None,
 }
 
+// f 本目の指で選んだ選択肢の番号
+fn choice(chosen: u64, f: usize) -> usize {
+    (chosen >> (CHOICE_BITS * f) & ((1 << CHOICE_BITS) - 1)) as usize
+}
+
 pub fn to_direction(x: usize) -> Direction {
     if x == 0 {
         Direction::Right
@@ -110,14 +125,17 @@ pub struct State {
     pub root: Coord,
     pub arm_direction: Vec<Direction>,
     pub finger_status: Vec<(FingerAction, FingerHas)>,
-    pub field: FxHashSet<Coord>,
+    pub field: Bitboard,
+    pub holding: Vec<Coord>,        // 指が掴んでいるモノの元の位置
+    pub remaining: Vec<[usize; 2]>, // ブロックごとの未完了の [掴む位置, 離す位置] の数
+    pub progress: usize,            // 計画に沿った進捗
     pub score: usize,
     pub hash: usize,
 }
 
 impl State {
     pub fn new(input: &Input) -> Self {
-        let mut field = FxHashSet::default();
+        let mut field = Bitboard::new(input.N);
         for i in 0..input.N {
             for j in 0..input.N {
                 if input.S[i][j] == '1' {
@@ -131,29 +149,83 @@ impl State {
             arm_direction: vec![Direction::Right; input.arm.lengths.len()],
             finger_status: vec![(FingerAction::Init, FingerHas::NotHas); input.arm.lengths.len()],
             field,
+            holding: vec![Coord::new(!0, !0); input.arm.lengths.len()],
+            remaining: input.plan.remaining.clone(),
+            progress: 0,
             score: 0,
             hash: input
                 .calc_hash
                 .init(input.N, input.V, &input.S, input.arm.start),
         }
     }
+    /// 今の指で行動できる、巡回順で最初のブロック
+    fn waypoint(&self, input: &Input) -> Option<usize> {
+        let has = |h: FingerHas| {
+            input
+                .arm
+                .fingers
+                .iter()
+                .any(|&idx| self.finger_status[idx].1 == h)
+        };
+        input
+            .plan
+            .waypoint(&self.remaining, has(FingerHas::NotHas), has(FingerHas::Has))
+    }
     pub fn is_done(&self, input: &Input, score: usize) -> bool {
         score == input.necessary_score
     }
+    /// 掴む・離すに加えて、割り当て通りに離す、巡回中のブロックで行動すると加点
+    fn progress_delta(
+        &self,
+        input: &Input,
+        finger_actions: &Vec<(FingerAction, FingerHas, Coord)>,
+        waypoint: Option<usize>,
+    ) -> usize {
+        let plan = &input.plan;
+        let mut ret = 0;
+        for (idx, &(action, _, coord)) in finger_actions.iter().enumerate() {
+            if action == FingerAction::Grab {
+                ret += input.grab_score * plan.score_weight() + plan.order_bonus(coord, waypoint);
+            } else if action == FingerAction::Release {
+                ret += input.release_score * plan.score_weight()
+                    + plan.match_bonus(self.holding[idx], coord)
+                    + plan.order_bonus(coord, waypoint);
+            }
+        }
+        ret
+    }
+    /// 進捗 + 根と巡回中のブロックの近さ
+    /// 完了した状態は必ず他より大きくなるようにする
+    fn eval(
+        &self,
+        input: &Input,
+        progress: usize,
+        root: Coord,
+        waypoint: Option<usize>,
+        is_done: bool,
+    ) -> usize {
+        let mut ret = progress + input.plan.position_score(root, waypoint);
+        if is_done {
+            ret += input.necessary_score * input.plan.score_weight();
+        }
+        ret
+    }
     pub fn cand(
         &self,
         input: &Input,
     ) -> Vec<(
         usize, // スコア
+        usize, // 評価値
         usize, // ハッシュ
         Op,
         bool, // is_done
     )> {
         // 直前で根以外が行動していなければ、反対方向へ移動可能
         let opposite = self.finger_status.iter().all(|x| x.0 == FingerAction::None);
-        let finger_parent_relative_position = input
+        let placements = input
             .arm
             .finger_parent_relative_position(&self.arm_direction, opposite);
+        let waypoint = self.waypoint(input);
         let mut cands = vec![];
 
         // 上下左右に根が動く、または停止
@@ -164,42 +236,57 @@ impl State {
             if !root_next.in_map(input.N) {
                 continue;
             }
-            let mut score_is_zero = false;
             let mut root_move_cands = vec![];
+            // 操作を作る前の候補 (スコア, 評価値, is_done, 配置, 指の回転, 指の行動)
+            let mut zero_cands = vec![];
+            let mut score_cands = vec![];
 
-            for (finger_parent_relative_pos, finger_parent_action) in
-                finger_parent_relative_position.iter()
+            // 根の移動のみ
             {
-                let finger_parent_pos = self.root + *finger_parent_relative_pos + delta;
-                // 指がついた腕に伝搬される累積回転数を求める
-                let rotate = finger_parent_action
-                    .iter()
-                    .fold(0, |sum, &x| (sum + x.0 as usize) % 4);
-
-                let mut score = 0;
-                let mut finger_rotate_actions_and_directions = vec![];
+                let mut actions_and_directions = vec![(move_action, Direction::None)];
+                for dir in self.arm_direction.iter() {
+                    actions_and_directions.push((MoveAction::None, dir.clone()));
+                }
                 let mut finger_actions = vec![];
-                // 指を持たない腕のアクションは何もしないで埋めておく
-                for _ in 0..input.arm.not_finger_arm_num {
-                    finger_actions.push((
-                        FingerAction::None,
-                        FingerHas::NotHas,
-                        Coord::new(!0, !0),
-                    ));
+                for (_, has) in self.finger_status.iter() {
+                    finger_actions.push((FingerAction::None, has.clone(), Coord::new(!0, !0)));
                 }
-                let mut used_coords = FxHashSet::default();
+                let hash = input
+                    .calc_hash
+                    .calc_root_position(self.hash, self.root, root_next);
+                let op = Op {
+                    move_actions: actions_and_directions,
+                    finger_actions,
+                };
+                let is_done = self.is_done(input, self.score);
+                let eval = self.eval(input, self.progress, root_next, waypoint, is_done);
+                root_move_cands.push((0, eval, hash, op, is_done));
+            }
+
+            // スコア0で関節を回転させる移動は、配置が散らばるように間引いて残す
+            let stride = (placements.len() / ZERO_CAND_NUM).max(1);
+            for (placement_idx, placement) in placements.iter().enumerate() {
+                // 指ごとの選択肢 (スコア, 回転, 回転後の向き, 行動, 持っているか, 座標)
+                // 先頭は何もしない選択肢
+                let mut options = vec![];
                 for idx in input.arm.fingers.iter() {
+                    let parent = input.arm.parents[*idx];
+                    let finger_parent_pos = self.root + placement.positions[parent] + delta;
+                    // 指がついた腕に伝搬される累積回転数
+                    let rotate = placement.rotates[parent];
                     let len = input.arm.lengths[*idx];
                     let dir: Direction =
                         to_direction((self.arm_direction[*idx] as usize + rotate) % 4);
                     let (finger_action, finger_has) = self.finger_status[*idx];
-                    let mut best_score = 0;
-                    let mut best_rotate_action = MoveAction::None;
-                    let mut best_finger_direction = dir;
-                    let mut best_finger_action = FingerAction::None;
-                    let mut best_finger_has = finger_has;
                     let delta = DIJ4[dir as usize] * Coord::new(len, len);
-                    let mut best_finger_coord = finger_parent_pos + delta;
+                    let mut finger_options = vec![(
+                        0,
+                        MoveAction::None,
+                        dir,
+                        FingerAction::None,
+                        finger_has,
+                        finger_parent_pos + delta,
+                    )];
 
                     for i in 0..=3 {
                         if i == 2 && finger_action != FingerAction::None {
@@ -210,76 +297,137 @@ impl State {
                         let next_dir: Direction = to_direction((dir as usize + i) % 4);
                         let delta = DIJ4[next_dir as usize] * Coord::new(len, len);
                         let finger_pos = finger_parent_pos + delta;
+                        if !finger_pos.in_map(input.N) {
+                            continue;
+                        }
 
                         // 掴んでいるモノを離す
                         if finger_has == FingerHas::Has
-                            && finger_pos.in_map(input.N)
-                            && !self.field.contains(&Coord::new(finger_pos.i, finger_pos.j))
+                            && !self.field.contains(finger_pos)
                             && input.T[finger_pos.i][finger_pos.j] == '1'
-                            && best_score < input.release_score
-                            && !used_coords.contains(&finger_pos)
                         {
-                            best_score = input.release_score;
-                            best_rotate_action = to_rotate_direction(i);
-                            best_finger_direction = next_dir;
-                            best_finger_action = FingerAction::Release;
-                            best_finger_has = FingerHas::NotHas;
-                            best_finger_coord = finger_pos;
+                            finger_options.push((
+                                input.release_score,
+                                to_rotate_direction(i),
+                                next_dir,
+                                FingerAction::Release,
+                                FingerHas::NotHas,
+                                finger_pos,
+                            ));
 
                         // 目的地に到達していないモノを掴む
                         } else if finger_has == FingerHas::NotHas
-                            && finger_pos.in_map(input.N)
-                            && self.field.contains(&Coord::new(finger_pos.i, finger_pos.j))
+                            && self.field.contains(finger_pos)
                             && input.T[finger_pos.i][finger_pos.j] == '0'
-                            && best_score < input.grab_score
-                            && !used_coords.contains(&finger_pos)
                         {
-                            best_score = input.grab_score;
-                            best_rotate_action = to_rotate_direction(i);
-                            best_finger_direction = next_dir;
-                            best_finger_action = FingerAction::Grab;
-                            best_finger_has = FingerHas::Has;
-                            best_finger_coord = finger_pos;
+                            finger_options.push((
+                                input.grab_score,
+                                to_rotate_direction(i),
+                                next_dir,
+                                FingerAction::Grab,
+                                FingerHas::Has,
+                                finger_pos,
+                            ));
                         }
                     }
-                    score += best_score;
-                    finger_rotate_actions_and_directions
-                        .push((best_rotate_action, best_finger_direction));
-                    finger_actions.push((best_finger_action, best_finger_has, best_finger_coord));
-                    used_coords.insert(best_finger_coord);
+                    options.push(finger_options);
                 }
 
-                // スコア0の場合は、根の移動以外は同一視
-                if score == 0 {
-                    if !score_is_zero {
-                        let mut actions_and_directions = vec![(move_action, Direction::None)];
-                        for dir in self.arm_direction.iter() {
-                            actions_and_directions.push((MoveAction::None, dir.clone()));
+                // 指の選択肢の組み合わせを、スコアの大きい順に ASSIGN_BUDGET 個まで残す
+                // (スコア, 選んだ選択肢)
+                // 選んだ選択肢は指ごとに CHOICE_BITS ビットずつ詰める
+                let mut assigns: Vec<(usize, u64)> = vec![(0, 0)];
+                let mut next_assigns = vec![];
+                for (f, finger_options) in options.iter().enumerate() {
+                    // 何もしない選択肢しかない指は組み合わせを増やさない
+                    if finger_options.len() == 1 {
+                        continue;
+                    }
+                    next_assigns.clear();
+                    for &(score, chosen) in assigns.iter() {
+                        for (k, option) in finger_options.iter().enumerate() {
+                            // 同じマスを複数の指で掴む・離すことはできない
+                            if k > 0
+                                && (0..f).any(|g| {
+                                    let c = choice(chosen, g);
+                                    c > 0 && options[g][c].5 == option.5
+                                })
+                            {
+                                continue;
+                            }
+                            let chosen = chosen | (k as u64) << (CHOICE_BITS * f);
+                            next_assigns.push((score + option.0, chosen));
                         }
-                        let mut finger_actions = vec![];
-                        for (_, has) in self.finger_status.iter() {
-                            finger_actions.push((
-                                FingerAction::None,
-                                has.clone(),
-                                Coord::new(!0, !0),
-                            ));
+                    }
+                    if next_assigns.len() > ASSIGN_BUDGET {
+                        next_assigns.sort_by_key(|a| Reverse(a.0));
+                        next_assigns.truncate(ASSIGN_BUDGET);
+                    }
+                    std::mem::swap(&mut assigns, &mut next_assigns);
+                }
+
+                let rotated = placement
+                    .actions
+                    .iter()
+                    .any(|&(action, _)| action != MoveAction::None);
+                // スコア0の組み合わせは、間引いた配置で何もしない選択肢の組み合わせだけを残す
+                assigns.retain(|a| a.0 > 0);
+                if rotated && placement_idx % stride == 0 {
+                    // 何もしない選択肢の組み合わせ
+                    assigns.push((0, 0));
+                }
+
+                for (score, chosen) in assigns {
+                    let mut finger_rotate_actions_and_directions = vec![];
+                    let mut finger_actions = vec![];
+                    // 指を持たない腕のアクションは何もしないで埋めておく
+                    for _ in 0..input.arm.not_finger_arm_num {
+                        finger_actions.push((
+                            FingerAction::None,
+                            FingerHas::NotHas,
+                            Coord::new(!0, !0),
+                        ));
+                    }
+                    for (f, finger_options) in options.iter().enumerate() {
+                        let k = choice(chosen, f);
+                        let (_, rotate_action, direction, mut finger_action, finger_has, coord) =
+                            finger_options[k];
+                        if score == 0 {
+                            // 次のターンで反対方向への回転をさせないため、Initで印をつける
+                            finger_action = FingerAction::Init;
                         }
-                        let hash = input
-                            .calc_hash
-                            .calc_root_position(self.hash, self.root, root_next);
-                        let op = Op {
-                            move_actions: actions_and_directions,
-                            finger_actions,
-                        };
-                        root_move_cands.push((0, hash, op, self.is_done(input, self.score)));
-                        score_is_zero = true;
+                        finger_rotate_actions_and_directions.push((rotate_action, direction));
+                        finger_actions.push((finger_action, finger_has, coord));
+                    }
+                    let is_done = self.is_done(input, self.score + score);
+                    let progress =
+                        self.progress + self.progress_delta(input, &finger_actions, waypoint);
+                    let eval = self.eval(input, progress, root_next, waypoint, is_done);
+                    let pending = (
+                        score,
+                        eval,
+                        is_done,
+                        placement_idx,
+                        finger_rotate_actions_and_directions,
+                        finger_actions,
+                    );
+                    if score == 0 {
+                        zero_cands.push(pending);
+                    } else {
+                        score_cands.push(pending);
                     }
-                    continue;
                 }
+            }
 
+            // 残す候補だけ操作とハッシュを作る
+            score_cands.sort_by_key(|c| Reverse(c.1));
+            score_cands.truncate(SCORE_CAND_NUM);
+            for (score, eval, is_done, placement_idx, finger_rotates, finger_actions) in
+                zero_cands.into_iter().chain(score_cands)
+            {
                 let mut rotate_actions = vec![(move_action, Direction::None)];
-                rotate_actions.extend(finger_parent_action.clone());
-                rotate_actions.extend(finger_rotate_actions_and_directions);
+                rotate_actions.extend(placements[placement_idx].actions.iter().cloned());
+                rotate_actions.extend(finger_rotates);
 
                 let field_change_coords: Vec<Coord> = finger_actions
                     .iter()
@@ -303,13 +451,15 @@ impl State {
                     move_actions: rotate_actions,
                     finger_actions,
                 };
-                root_move_cands.push((score, hash, op, self.is_done(input, self.score + score)));
+                root_move_cands.push((score, eval, hash, op, is_done));
             }
             cands.extend(root_move_cands);
         }
         cands
     }
-    pub fn apply(&mut self, score: usize, hash: usize, op: &Op) {
+    pub fn apply(&mut self, input: &Input, score: usize, hash: usize, op: &Op) {
+        let waypoint = self.waypoint(input);
+        self.progress += self.progress_delta(input, &op.finger_actions, waypoint);
         self.root = self.root + DIJ5[move_action_to_direction(op.move_actions[0].0) as usize];
         self.arm_direction = op
             .move_actions
@@ -324,14 +474,28 @@ impl State {
             .cloned()
             .map(|x| (x.0, x.1))
             .collect::<Vec<(FingerAction, FingerHas)>>();
-        for (finger_action, _, coord) in op.finger_actions.iter() {
-            if *finger_action == FingerAction::Grab {
-                self.field.remove(&Coord::new(coord.i, coord.j));
+        for (idx, (finger_action, _, coord)) in op.finger_actions.iter().enumerate() {
+            let kind = if *finger_action == FingerAction::Grab {
+                self.field.remove(*coord);
+                self.holding[idx] = *coord;
+                0
             } else if *finger_action == FingerAction::Release {
-                self.field.insert(Coord::new(coord.i, coord.j));
-            }
+                self.field.insert(*coord);
+                self.holding[idx] = Coord::new(!0, !0);
+                1
+            } else {
+                continue;
+            };
+            let c = input.plan.cluster[coord.i][coord.j];
+            self.remaining[c][kind] = self.remaining[c][kind].saturating_sub(1);
         }
         self.score = score;
         self.hash = hash;
+        debug_assert_eq!(
+            self.hash,
+            input
+                .calc_hash
+                .calc_from_scratch(input.N, &self.field, self.root, &self.arm_direction)
+        );
     }
 }
@@ -22,8 +22,8 @@ impl Coord {
     pub fn new(x: usize, y: usize) -> Self {
         Self { x, y }
     }
-    pub fn in_map(self, size: usize) -> bool {
-        self.x < size && self.y < size
+    pub fn in_map(self, w: usize, h: usize) -> bool {
+        self.x < w && self.y < h
     }
 }
 
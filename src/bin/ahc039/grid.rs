@@ -0,0 +1,76 @@
+/// 不均一なグリッドの1軸分の境界
+/// セル i は [bounds[i], bounds[i + 1]) で、先頭は 0、末尾は size (最後のセルは size も含む)
+/// 実座標では選んだセルの上側の辺を 1 下げるので、セルの幅は 2 以上にする
+#[derive(Debug, Clone)]
+pub struct Axis {
+    pub bounds: Vec<usize>,
+}
+
+impl Axis {
+    pub fn uniform(size: usize, num: usize) -> Self {
+        Self {
+            bounds: (0..=num).map(|i| size * i / num).collect(),
+        }
+    }
+    /// 魚の座標を圧縮し、各セルの魚の数がおおよそ等しくなるように num 個のセルにまとめる
+    /// 同じ座標が多くセルが足りない場合は、幅の大きいセルを二分して num 個にそろえる
+    pub fn compressed(mut values: Vec<usize>, size: usize, num: usize) -> Self {
+        values.sort();
+        let mut bounds = vec![0];
+        if !values.is_empty() {
+            for k in 1..num {
+                let v = values[k * values.len() / num];
+                if v >= *bounds.last().unwrap() + 2 && v + 2 <= size {
+                    bounds.push(v);
+                }
+            }
+        }
+        bounds.push(size);
+        while bounds.len() < num + 1 {
+            let i = (0..bounds.len() - 1)
+                .max_by_key(|&i| bounds[i + 1] - bounds[i])
+                .unwrap();
+            if bounds[i + 1] - bounds[i] < 4 {
+                break;
+            }
+            bounds.insert(i + 1, (bounds[i] + bounds[i + 1]) / 2);
+        }
+        Self { bounds }
+    }
+    pub fn num(&self) -> usize {
+        self.bounds.len() - 1
+    }
+    pub fn width(&self, i: usize) -> usize {
+        self.bounds[i + 1] - self.bounds[i]
+    }
+    pub fn center(&self, i: usize) -> usize {
+        (self.bounds[i] + self.bounds[i + 1]) / 2
+    }
+    /// v を含むセル (v == size は最後のセル)
+    pub fn index(&self, v: usize) -> usize {
+        self.bounds
+            .partition_point(|&b| b <= v)
+            .saturating_sub(1)
+            .min(self.num() - 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Axis;
+
+    #[test]
+    fn test_compressed_axis() {
+        let values = vec![10, 10, 10, 11, 12, 50, 900, 901];
+        let axis = Axis::compressed(values, 1000, 4);
+        assert_eq!(axis.num(), 4);
+        assert_eq!(axis.bounds[0], 0);
+        assert_eq!(axis.bounds[4], 1000);
+        assert!(axis.bounds.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(axis.index(0), 0);
+        assert_eq!(axis.index(1000), 3);
+        let uniform = Axis::uniform(100, 4);
+        assert_eq!(uniform.bounds, vec![0, 25, 50, 75, 100]);
+        assert_eq!(uniform.index(50), 2);
+    }
+}
@@ -3,21 +3,30 @@
 
 mod common;
 mod coord;
+mod grid;
 mod input;
 mod polygon;
+mod polygon_opt;
 mod state;
 mod test;
+#[path = "../utils/trajectory.rs"]
+mod trajectory;
+mod validate;
 
 use common::{connect9, get_time};
 use input::{read_input, Input};
-use polygon::polygon_grid_to_vertex_coords;
+use polygon::{polygon_grid_to_vertex_coords, to_real_coords};
+use polygon_opt::{exact_score, PolygonState};
 use rand_pcg::Pcg64Mcg;
 use state::State;
+use validate::{make_valid, validate};
 
 fn solve(input: &Input) {
     let mut rng = Pcg64Mcg::new(10);
     let connect9 = connect9();
-    let tle_list = vec![0.5, 1.0, 1.5, 1.7, 1.95];
+    let tle_list = vec![0.5, 1.0, 1.4, 1.6, 1.75];
+    // 頂点を直接動かす焼きなまし
+    let vertex_tle = 1.95;
     let grid_num_list = vec![25, 50, 100, 200, 400];
     let mut state = State::new(grid_num_list[0], input);
     state.annealing(&mut rng, &connect9, tle_list[0]);
@@ -27,20 +36,33 @@ fn solve(input: &Input) {
         state.annealing(&mut rng, &connect9, tle_list[i]);
     }
     let polygon = polygon_grid_to_vertex_coords(&state.best_grid);
+    let polygon = to_real_coords(&polygon, &state.best_grid, &state.best_xs, &state.best_ys);
+    let mut polygon_state = PolygonState::new(&polygon, input);
+    let grid_score = polygon_state.score;
+    polygon_state.annealing(&mut rng, vertex_tle, input, &mut state.trajectory);
+    // best_score は厳密に数えたスコア
+    let polygon = if polygon_state.best_score > grid_score {
+        polygon_state.best_vs
+    } else {
+        polygon
+            .iter()
+            .map(|p| (p.x as i64, p.y as i64))
+            .collect::<Vec<_>>()
+    };
+
+    let polygon = make_valid(polygon, input);
+    let length = validate(&polygon).unwrap();
     println!("{}", polygon.len());
     for p in polygon.iter() {
-        println!(
-            "{} {}",
-            p.x * state.best_dl as usize,
-            p.y * state.best_dl as usize
-        );
+        println!("{} {}", p.0, p.1);
     }
-    eprintln!("Length = {}", state.best_length);
-    eprintln!("Score = {}", state.best_score);
+    eprintln!("Length = {}", length);
+    eprintln!("Score = {}", exact_score(&polygon, input));
+    state.trajectory.save();
 }
 
 fn vis(grid: &Vec<Vec<bool>>) {
-    for y in (0..grid.len()).rev() {
+    for y in (0..grid[0].len()).rev() {
         for x in 0..grid.len() {
             if grid[x][y] {
                 eprint!("■ ");
@@ -1,6 +1,9 @@
 use itertools::iproduct;
 
-use crate::coord::{Coord, DXY4, TWO_BY_TWO};
+use crate::{
+    coord::{Coord, DXY4, TWO_BY_TWO},
+    grid::Axis,
+};
 
 #[derive(Debug, Clone, Copy)]
 enum Direction {
@@ -29,12 +32,12 @@ fn usize_to_edge(n: usize) -> Edge {
 }
 
 pub fn polygon_grid_to_vertex_coords(grid: &Vec<Vec<bool>>) -> Vec<Coord> {
-    let n = grid.len();
+    let (w, h) = (grid.len(), grid[0].len());
     let mut ret = vec![];
     // 左下から時計回りに辿る
     let start = {
         let mut start = None;
-        for (y, x) in iproduct!(0..n + 1, 0..n + 1) {
+        for (y, x) in iproduct!(0..h + 1, 0..w + 1) {
             if is_vertex(Coord::new(x, y), grid) {
                 start = Some(Coord::new(x, y));
                 break;
@@ -80,19 +83,51 @@ pub fn polygon_grid_to_vertex_coords(grid: &Vec<Vec<bool>>) -> Vec<Coord> {
     ret
 }
 
+// グリッドの頂点番号を実際の座標に変換する
+// セル i は整数座標 bounds[i]..bounds[i + 1] - 1 を持つので、選んだセルが境界の下側にあれば辺を 1 つ下げる
+// こうすると境界上の魚 (辺上は内側扱い) もグリッドと同じセルに数えられる
+pub fn to_real_coords(
+    polygon: &Vec<Coord>,
+    grid: &Vec<Vec<bool>>,
+    xs: &Axis,
+    ys: &Axis,
+) -> Vec<Coord> {
+    let (w, h) = (grid.len(), grid[0].len());
+    polygon
+        .iter()
+        .map(|&p| {
+            // 1個だけ選ばれていればそのセル、3個ならば選ばれていないセルの反対側が内側
+            let cnt = count_two_by_two(p, grid);
+            let dxy = TWO_BY_TWO
+                .iter()
+                .find(|&&dxy| {
+                    let nxt = p + dxy;
+                    let selected = nxt.in_map(w, h) && grid[nxt.x][nxt.y];
+                    selected == (cnt == 1)
+                })
+                .unwrap();
+            let lower_x = (dxy.x == !0) == (cnt == 1);
+            let lower_y = (dxy.y == !0) == (cnt == 1);
+            let x = xs.bounds[p.x] - (lower_x && p.x < xs.num()) as usize;
+            let y = ys.bounds[p.y] - (lower_y && p.y < ys.num()) as usize;
+            Coord::new(x, y)
+        })
+        .collect()
+}
+
 // 2x2の領域に頂点があるかどうか
-fn is_vertex(coord: Coord, grid: &Vec<Vec<bool>>) -> bool {
+pub fn is_vertex(coord: Coord, grid: &Vec<Vec<bool>>) -> bool {
     let cnt = count_two_by_two(coord, grid);
     cnt == 1 || cnt == 3
 }
 
 // 2x2の領域のタイルの数を数える
 fn count_two_by_two(coord: Coord, grid: &Vec<Vec<bool>>) -> usize {
-    let n = grid.len();
+    let (w, h) = (grid.len(), grid[0].len());
     let mut cnt = 0;
     for dxy in TWO_BY_TWO.iter() {
         let nxt = coord + *dxy;
-        if nxt.in_map(n) {
+        if nxt.in_map(w, h) {
             cnt += grid[nxt.x][nxt.y] as usize;
         }
     }
@@ -101,12 +136,12 @@ fn count_two_by_two(coord: Coord, grid: &Vec<Vec<bool>>) -> usize {
 
 // 2x2の領域の辺の種類を返す
 fn to_edge(coord: Coord, grid: &Vec<Vec<bool>>) -> Edge {
-    let n = grid.len();
+    let (w, h) = (grid.len(), grid[0].len());
     let cnt = count_two_by_two(coord, grid);
     if cnt == 1 {
         for (i, dxy) in TWO_BY_TWO.iter().enumerate() {
             let nxt = coord + *dxy;
-            if nxt.in_map(n) && grid[nxt.x][nxt.y] {
+            if nxt.in_map(w, h) && grid[nxt.x][nxt.y] {
                 return usize_to_edge(i);
             }
         }
@@ -115,7 +150,7 @@ fn to_edge(coord: Coord, grid: &Vec<Vec<bool>>) -> Edge {
     if cnt == 3 {
         for (i, dxy) in TWO_BY_TWO.iter().enumerate() {
             let nxt = coord + *dxy;
-            if nxt.in_map(n) && !grid[nxt.x][nxt.y] {
+            if nxt.in_map(w, h) && !grid[nxt.x][nxt.y] {
                 return usize_to_edge(i);
             }
         }
@@ -132,9 +167,14 @@ mod tests {
     use colored::*;
     use itertools::iproduct;
 
-    use crate::coord::{calc_manhattan_dist, Coord};
+    use crate::{
+        coord::{calc_manhattan_dist, Coord},
+        grid::Axis,
+        input::Input,
+        polygon_opt::exact_score,
+    };
 
-    use super::polygon_grid_to_vertex_coords;
+    use super::{polygon_grid_to_vertex_coords, to_real_coords};
 
     #[test]
     fn polygon() {
@@ -194,4 +234,45 @@ mod tests {
         }
         assert!(edge_sum == 32);
     }
+
+    #[test]
+    fn test_boundary_fish() {
+        // L字の領域で、境界上の魚がグリッドと同じセルに数えられるか
+        let mut grid = vec![vec![false; 4]; 4];
+        grid[1][1] = true;
+        grid[2][1] = true;
+        grid[1][2] = true;
+        let xs = Axis::uniform(100, 4);
+        let ys = Axis::uniform(100, 4);
+        let saba = vec![Coord::new(25, 25), Coord::new(74, 49), Coord::new(49, 74)];
+        let iwashi = vec![
+            Coord::new(75, 30),
+            Coord::new(50, 50),
+            Coord::new(30, 75),
+            Coord::new(24, 40),
+        ];
+        let input = Input {
+            N: saba.len(),
+            size: 100,
+            cmin: 0,
+            cmax: 100,
+            saba,
+            iwashi,
+        };
+        let mut grid_score = 0;
+        for p in input.saba.iter() {
+            grid_score += grid[xs.index(p.x)][ys.index(p.y)] as i64;
+        }
+        for p in input.iwashi.iter() {
+            grid_score -= grid[xs.index(p.x)][ys.index(p.y)] as i64;
+        }
+        assert_eq!(grid_score, 3);
+
+        let polygon = polygon_grid_to_vertex_coords(&grid);
+        let polygon = to_real_coords(&polygon, &grid, &xs, &ys)
+            .iter()
+            .map(|p| (p.x as i64, p.y as i64))
+            .collect::<Vec<_>>();
+        assert_eq!(exact_score(&polygon, &input), grid_score);
+    }
 }
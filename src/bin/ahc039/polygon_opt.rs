@@ -0,0 +1,662 @@
+use rand::Rng;
+use rand_pcg::Pcg64Mcg;
+
+use crate::{common::get_time, coord::Coord, input::Input, trajectory::Trajectory};
+
+const LENGTH_LIMIT: i64 = 4e5 as i64;
+const VERTEX_LIMIT: usize = 1000;
+const SIZE: i64 = 1e5 as i64;
+
+/// x座標順に並べた点を葉に持ち、各ノードに y座標のソート列を持つ (merge sort tree)
+pub struct RectCounter {
+    xs: Vec<i64>,
+    n: usize,
+    tree: Vec<Vec<i64>>,
+}
+
+impl RectCounter {
+    pub fn new(points: &Vec<Coord>) -> Self {
+        let mut ps = points
+            .iter()
+            .map(|p| (p.x as i64, p.y as i64))
+            .collect::<Vec<_>>();
+        ps.sort();
+        let mut n = 1;
+        while n < ps.len() {
+            n *= 2;
+        }
+        let mut tree = vec![vec![]; 2 * n];
+        for (i, p) in ps.iter().enumerate() {
+            tree[n + i].push(p.1);
+        }
+        for k in (1..n).rev() {
+            let mut v = tree[2 * k].clone();
+            v.extend(tree[2 * k + 1].iter());
+            v.sort();
+            tree[k] = v;
+        }
+        Self {
+            xs: ps.iter().map(|p| p.0).collect(),
+            n,
+            tree,
+        }
+    }
+    /// [x1, x2] × [y1, y2] に含まれる点の数
+    pub fn count(&self, x1: i64, x2: i64, y1: i64, y2: i64) -> i64 {
+        if x1 > x2 || y1 > y2 {
+            return 0;
+        }
+        let mut l = self.xs.partition_point(|&x| x < x1) + self.n;
+        let mut r = self.xs.partition_point(|&x| x <= x2) + self.n;
+        let cnt = |v: &Vec<i64>| {
+            (v.partition_point(|&y| y <= y2) - v.partition_point(|&y| y < y1)) as i64
+        };
+        let mut ret = 0;
+        while l < r {
+            if l & 1 == 1 {
+                ret += cnt(&self.tree[l]);
+                l += 1;
+            }
+            if r & 1 == 1 {
+                r -= 1;
+                ret += cnt(&self.tree[r]);
+            }
+            l >>= 1;
+            r >>= 1;
+        }
+        ret
+    }
+}
+
+/// 頂点列を直接動かす焼きなまし
+/// 辺を平行移動する、辺に凹凸を追加する、隣の辺を潰して同一直線上の頂点をまとめる
+/// スコアの差分は移動で掃いた長方形内の魚の数から求める (境界上の魚は近似なので、最良を更新するときに厳密に数え直す)
+pub struct PolygonState {
+    pub vs: Vec<(i64, i64)>,
+    pub length: i64,
+    pub score: i64,
+    pub best_vs: Vec<(i64, i64)>,
+    pub best_score: i64,
+    orient: i64, // 反時計回りなら1, 時計回りなら-1
+    saba: RectCounter,
+    iwashi: RectCounter,
+}
+
+impl PolygonState {
+    pub fn new(polygon: &Vec<Coord>, input: &Input) -> Self {
+        let vs = polygon
+            .iter()
+            .map(|p| (p.x as i64, p.y as i64))
+            .collect::<Vec<_>>();
+        let mut area2 = 0;
+        for i in 0..vs.len() {
+            let (a, b) = (vs[i], vs[(i + 1) % vs.len()]);
+            area2 += a.0 * b.1 - a.1 * b.0;
+        }
+        let score = exact_score(&vs, input);
+        Self {
+            length: calc_length(&vs),
+            best_vs: vs.clone(),
+            vs,
+            score,
+            best_score: score,
+            orient: if area2 > 0 { 1 } else { -1 },
+            saba: RectCounter::new(&input.saba),
+            iwashi: RectCounter::new(&input.iwashi),
+        }
+    }
+    pub fn annealing(
+        &mut self,
+        rng: &mut Pcg64Mcg,
+        tle: f64,
+        input: &Input,
+        trajectory: &mut Trajectory,
+    ) {
+        if self.vs.len() < 4 {
+            return;
+        }
+        let start = get_time();
+        let T0 = 1.0;
+        let T1 = 0.1;
+        let mut iter = 0;
+        // 近似のスコアでの最良
+        let mut best_approx = self.score;
+        loop {
+            let time = get_time();
+            if time >= tle {
+                break;
+            }
+            let progress = (time - start) / (tle - start);
+            let temp = T0 + (T1 - T0) * progress;
+            // 移動量は時間とともに小さくする
+            let max_d = (5000.0 * (1.0 - progress)).max(20.0) as i64;
+
+            let n = self.vs.len();
+            let i = rng.gen_range(0..n);
+            let r = rng.gen_range(0..10);
+            let next = if r < 6 {
+                let mut d = rng.gen_range(1..=max_d);
+                if rng.gen_bool(0.5) {
+                    d = -d;
+                }
+                self.slide(i, d)
+            } else if r < 9 {
+                self.notch(i, max_d, rng)
+            } else {
+                // 隣の辺の長さ分だけ動かして潰す
+                let (a, b, q) = (self.vs[i], self.vs[(i + 1) % n], self.vs[(i + 2) % n]);
+                let d = if a.1 == b.1 { q.1 - b.1 } else { q.0 - b.0 };
+                self.slide(i, d)
+            };
+            let Some((vs, length, diff_score)) = next else {
+                continue;
+            };
+            let accepted = diff_score >= 0 || rng.gen_bool((diff_score as f64 / temp).exp());
+            if accepted {
+                iter += 1;
+                self.vs = vs;
+                self.length = length;
+                self.score += diff_score;
+                // 近似のスコアで最良を超えたら厳密に数え直し、厳密なスコアでも良ければ更新する
+                if self.score > best_approx {
+                    best_approx = self.score;
+                    let score = exact_score(&self.vs, input);
+                    if score > self.best_score {
+                        self.best_score = score;
+                        self.best_vs = self.vs.clone();
+                    }
+                }
+            }
+            trajectory.push(time, 0, self.best_score, self.score, temp, accepted);
+        }
+        eprintln!("Vertex Iter = {}", iter);
+    }
+    /// 平行移動で掃いた帯 (辺の位置 c から c + d) に含まれる魚の差分
+    /// 外側へ動かすときは新しい辺上を、内側へ動かすときは元の辺上を含める
+    fn strip_score(
+        &self,
+        horizontal: bool,
+        c: i64,
+        d: i64,
+        lo: i64,
+        hi: i64,
+        outward: bool,
+    ) -> i64 {
+        let (c1, c2) = match (outward, d > 0) {
+            (true, true) => (c + 1, c + d),
+            (true, false) => (c + d, c - 1),
+            (false, true) => (c, c + d - 1),
+            (false, false) => (c + d + 1, c),
+        };
+        let val = if horizontal {
+            self.saba.count(lo, hi, c1, c2) - self.iwashi.count(lo, hi, c1, c2)
+        } else {
+            self.saba.count(c1, c2, lo, hi) - self.iwashi.count(c1, c2, lo, hi)
+        };
+        if outward {
+            val
+        } else {
+            -val
+        }
+    }
+    /// 辺 i を平行移動したときのスコアの差分
+    fn slide_score(&self, i: usize, d: i64) -> i64 {
+        let n = self.vs.len();
+        let (va, vb) = (self.vs[i], self.vs[(i + 1) % n]);
+        let horizontal = va.1 == vb.1;
+        let (c, lo, hi) = if horizontal {
+            (va.1, va.0.min(vb.0), va.0.max(vb.0))
+        } else {
+            (va.0, va.1.min(vb.1), va.1.max(vb.1))
+        };
+        self.strip_score(horizontal, c, d, lo, hi, self.is_outward(va, vb, d))
+    }
+    /// 辺 i を動かして、次の辺 (forward) または前の辺を長さ0にする移動量
+    fn collapse_d(&self, i: usize, forward: bool) -> i64 {
+        let n = self.vs.len();
+        let (va, vb) = (self.vs[i], self.vs[(i + 1) % n]);
+        let other = if forward {
+            self.vs[(i + 2) % n]
+        } else {
+            self.vs[(i + n - 1) % n]
+        };
+        if va.1 == vb.1 {
+            other.1 - va.1
+        } else {
+            other.0 - va.0
+        }
+    }
+    /// 頂点数と周長の制限を満たすまで、スコアの減少が最も小さい段を潰す
+    /// 周長が超えている間は、周長が減る段を優先する
+    pub fn repair(&mut self) -> bool {
+        while self.vs.len() > VERTEX_LIMIT || self.length > LENGTH_LIMIT {
+            let n = self.vs.len();
+            let length_over = self.length > LENGTH_LIMIT;
+            let mut cands = vec![];
+            for i in 0..n {
+                for forward in [false, true] {
+                    let d = self.collapse_d(i, forward);
+                    let (p, q) = (self.vs[(i + n - 1) % n], self.vs[(i + 2) % n]);
+                    let (va, vb) = (self.vs[i], self.vs[(i + 1) % n]);
+                    // 隣の辺の長さの変化
+                    let diff_length = if va.1 == vb.1 {
+                        (va.1 + d - p.1).abs() - (va.1 - p.1).abs() + (q.1 - vb.1 - d).abs()
+                            - (q.1 - vb.1).abs()
+                    } else {
+                        (va.0 + d - p.0).abs() - (va.0 - p.0).abs() + (q.0 - vb.0 - d).abs()
+                            - (q.0 - vb.0).abs()
+                    };
+                    let key = (length_over && diff_length > 0, -self.slide_score(i, d));
+                    cands.push((key, i, d));
+                }
+            }
+            cands.sort();
+            let mut applied = false;
+            for (_, i, d) in cands {
+                if let Some((vs, length, diff_score)) = self.slide(i, d) {
+                    if vs.len() < n || length < self.length {
+                        self.vs = vs;
+                        self.length = length;
+                        self.score += diff_score;
+                        applied = true;
+                        break;
+                    }
+                }
+            }
+            if !applied {
+                return false;
+            }
+        }
+        true
+    }
+    /// 有向辺 a -> b を、辺と垂直な方向に d 動かすと外側へ広がるか
+    fn is_outward(&self, a: (i64, i64), b: (i64, i64), d: i64) -> bool {
+        // 左法線 (-dy, dx) との内積
+        let dot = if a.1 == b.1 {
+            d * (b.0 - a.0)
+        } else {
+            -d * (b.1 - a.1)
+        };
+        // 反時計回りなら内部は左側
+        (dot > 0) != (self.orient == 1)
+    }
+    /// 辺 i (vs[i] -> vs[i + 1]) を平行移動する
+    /// 戻り値は (頂点列, 周長, スコアの差分)
+    fn slide(&self, i: usize, d: i64) -> Option<(Vec<(i64, i64)>, i64, i64)> {
+        let n = self.vs.len();
+        if d == 0 {
+            return None;
+        }
+        let (p, a, b, q) = ((i + n - 1) % n, i, (i + 1) % n, (i + 2) % n);
+        let (va, vb) = (self.vs[a], self.vs[b]);
+        let horizontal = va.1 == vb.1;
+        let get = |v: (i64, i64)| if horizontal { v.1 } else { v.0 };
+        let c = get(va);
+        if c + d < 0 || c + d > SIZE {
+            return None;
+        }
+        // 隣の辺の符号付き長さ
+        let sp = c - get(self.vs[p]);
+        let sq = get(self.vs[q]) - c;
+        let (np, nq) = (sp + d, sq - d);
+        // 隣の辺が反転するものは不可
+        if (np != 0 && np.signum() != sp.signum()) || (nq != 0 && nq.signum() != sq.signum()) {
+            return None;
+        }
+        let length = self.length + np.abs() - sp.abs() + nq.abs() - sq.abs();
+        if length > LENGTH_LIMIT && length > self.length {
+            return None;
+        }
+
+        let mut vs = self.vs.clone();
+        if horizontal {
+            vs[a].1 += d;
+            vs[b].1 += d;
+        } else {
+            vs[a].0 += d;
+            vs[b].0 += d;
+        }
+        let (lo, hi) = if horizontal {
+            (va.0.min(vb.0), va.0.max(vb.0))
+        } else {
+            (va.1.min(vb.1), va.1.max(vb.1))
+        };
+        let diff_score = self.strip_score(horizontal, c, d, lo, hi, self.is_outward(va, vb, d));
+        // 掃いた長方形に他の辺が入ると、差分が帯の中の魚の数と合わず、多角形も壊れる
+        let (c0, c1) = (c.min(c + d), c.max(c + d));
+        for j in 0..n {
+            if [(p + n - 1) % n, p, a, b, q].contains(&j) {
+                continue;
+            }
+            let (u, w) = (self.vs[j], self.vs[(j + 1) % n]);
+            let (u, w) = if horizontal {
+                (u, w)
+            } else {
+                ((u.1, u.0), (w.1, w.0))
+            };
+            if u.0.min(w.0) <= hi && lo <= u.0.max(w.0) && u.1.min(w.1) <= c1 && c0 <= u.1.max(w.1)
+            {
+                return None;
+            }
+        }
+
+        if np == 0 || nq == 0 {
+            // 長さ0になった辺の両端を取り除き、同一直線上の辺をまとめる
+            let mut remove = vec![];
+            if np == 0 {
+                remove.extend([p, a]);
+            }
+            if nq == 0 {
+                remove.extend([b, q]);
+            }
+            if n < remove.len() + 4 {
+                return None;
+            }
+            let keep = (0..n).filter(|k| !remove.contains(k)).collect::<Vec<_>>();
+            let vs = keep.iter().map(|&k| vs[k]).collect::<Vec<_>>();
+            // 変化したのは辺 i の周辺から始まる辺のみ
+            let idxs = keep
+                .iter()
+                .enumerate()
+                .filter(|&(_, &k)| (k + n - i) % n <= 3 || (i + n - k) % n <= 3)
+                .map(|(new_k, _)| new_k)
+                .collect::<Vec<_>>();
+            if !is_simple_around(&vs, &idxs) {
+                return None;
+            }
+            let length = calc_length(&vs);
+            if length > LENGTH_LIMIT && length > self.length {
+                return None;
+            }
+            return Some((vs, length, diff_score));
+        }
+
+        if !is_simple_around(&vs, &[p, a, b]) {
+            return None;
+        }
+        Some((vs, length, diff_score))
+    }
+    /// 辺 i の途中に深さ d の凹凸を追加する (頂点が4つ増える)
+    fn notch(
+        &self,
+        i: usize,
+        max_d: i64,
+        rng: &mut Pcg64Mcg,
+    ) -> Option<(Vec<(i64, i64)>, i64, i64)> {
+        let n = self.vs.len();
+        if n + 4 > VERTEX_LIMIT {
+            return None;
+        }
+        let (va, vb) = (self.vs[i], self.vs[(i + 1) % n]);
+        let horizontal = va.1 == vb.1;
+        let (lo, hi) = if horizontal {
+            (va.0.min(vb.0), va.0.max(vb.0))
+        } else {
+            (va.1.min(vb.1), va.1.max(vb.1))
+        };
+        if hi - lo < 3 {
+            return None;
+        }
+        let mut s = rng.gen_range(lo + 1..hi);
+        let mut t = rng.gen_range(lo + 1..hi);
+        if s == t {
+            return None;
+        }
+        if s > t {
+            std::mem::swap(&mut s, &mut t);
+        }
+        let mut d = rng.gen_range(1..=max_d);
+        if rng.gen_bool(0.5) {
+            d = -d;
+        }
+        let c = if horizontal { va.1 } else { va.0 };
+        if c + d < 0 || c + d > SIZE {
+            return None;
+        }
+        let length = self.length + 2 * d.abs();
+        if length > LENGTH_LIMIT {
+            return None;
+        }
+        // 辺の向きに沿って s, t の順に並べる
+        let (u, w) = if (horizontal && va.0 < vb.0) || (!horizontal && va.1 < vb.1) {
+            (s, t)
+        } else {
+            (t, s)
+        };
+        let point = |along: i64, across: i64| {
+            if horizontal {
+                (along, across)
+            } else {
+                (across, along)
+            }
+        };
+        let notch = [point(u, c), point(u, c + d), point(w, c + d), point(w, c)];
+        let mut vs = self.vs.clone();
+        for (k, v) in notch.iter().enumerate() {
+            vs.insert(i + 1 + k, *v);
+        }
+        if !is_simple_around(&vs, &[i + 1, i + 2, i + 3]) {
+            return None;
+        }
+        // 外側への凸は両端を含め、内側への凹は両端の辺上の魚が残るので含めない
+        let outward = self.is_outward(va, vb, d);
+        let diff_score = if outward {
+            self.strip_score(horizontal, c, d, s, t, true)
+        } else {
+            self.strip_score(horizontal, c, d, s + 1, t - 1, false)
+        };
+        Some((vs, length, diff_score))
+    }
+}
+
+pub fn calc_length(vs: &Vec<(i64, i64)>) -> i64 {
+    let n = vs.len();
+    (0..n)
+        .map(|i| {
+            let (a, b) = (vs[i], vs[(i + 1) % n]);
+            (a.0 - b.0).abs() + (a.1 - b.1).abs()
+        })
+        .sum()
+}
+
+// 軸に平行な2つの線分が共有点を持つか
+fn intersect(a: (i64, i64), b: (i64, i64), c: (i64, i64), d: (i64, i64)) -> bool {
+    a.0.min(b.0) <= c.0.max(d.0)
+        && c.0.min(d.0) <= a.0.max(b.0)
+        && a.1.min(b.1) <= c.1.max(d.1)
+        && c.1.min(d.1) <= a.1.max(b.1)
+}
+
+/// 辺 idxs が長さ正で、隣接しない辺と共有点を持たないか
+pub fn is_simple_around(vs: &Vec<(i64, i64)>, idxs: &[usize]) -> bool {
+    let n = vs.len();
+    for &i in idxs {
+        let (a, b) = (vs[i], vs[(i + 1) % n]);
+        if a == b {
+            return false;
+        }
+        for j in 0..n {
+            if j == i || j == (i + 1) % n || (j + 1) % n == i {
+                continue;
+            }
+            if intersect(a, b, vs[j], vs[(j + 1) % n]) {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+pub fn is_simple(vs: &Vec<(i64, i64)>) -> bool {
+    let n = vs.len();
+    // 辺は水平と垂直が交互に並ぶ
+    for i in 0..n {
+        let (a, b, c) = (vs[i], vs[(i + 1) % n], vs[(i + 2) % n]);
+        if (a.0 == b.0) == (b.0 == c.0) || (a.1 == b.1) == (b.1 == c.1) {
+            return false;
+        }
+    }
+    is_simple_around(vs, &(0..n).collect::<Vec<_>>())
+}
+
+/// 境界上も内部として数えた、サバの数 - イワシの数
+/// 頂点の座標を圧縮し、座標の上 (奇数) と座標の間 (偶数) を別のセルにしたグリッドで内外を塗る
+/// 内外は右向きの半直線と垂直な辺の交差の偶奇で決める (座標の上の行は1つ上の行と同じ)
+pub fn exact_score(vs: &Vec<(i64, i64)>, input: &Input) -> i64 {
+    let compress = |cs: Vec<i64>| {
+        let mut cs = cs;
+        cs.sort();
+        cs.dedup();
+        cs
+    };
+    let xs = compress(vs.iter().map(|v| v.0).collect());
+    let ys = compress(vs.iter().map(|v| v.1).collect());
+    let index = |cs: &Vec<i64>, c: i64| match cs.binary_search(&c) {
+        Ok(i) => 2 * i + 1,
+        Err(i) => 2 * i,
+    };
+    let (w, h) = (2 * xs.len() + 1, 2 * ys.len() + 1);
+    let mut boundary = vec![vec![false; h]; w];
+    // 行 j を横切る垂直な辺の列
+    let mut crossing = vec![vec![]; h];
+    for i in 0..vs.len() {
+        let (a, b) = (vs[i], vs[(i + 1) % vs.len()]);
+        let (ax, ay, bx, by) = (
+            index(&xs, a.0),
+            index(&ys, a.1),
+            index(&xs, b.0),
+            index(&ys, b.1),
+        );
+        for col in boundary[ax.min(bx)..=ax.max(bx)].iter_mut() {
+            col[ay.min(by)..=ay.max(by)].fill(true);
+        }
+        if ax == bx {
+            for y in (ay.min(by) + 1..ay.max(by)).step_by(2) {
+                crossing[y].push(ax);
+            }
+        }
+    }
+    let mut inside = vec![vec![false; h]; w];
+    for y in (0..h).step_by(2) {
+        crossing[y].sort();
+        // 左から見た辺の数の偶奇で、x より右にある辺の数の偶奇を求める
+        let mut k = 0;
+        for x in 0..w {
+            while k < crossing[y].len() && crossing[y][k] <= x {
+                k += 1;
+            }
+            inside[x][y] = boundary[x][y] || (crossing[y].len() - k) % 2 == 1;
+        }
+    }
+    for y in (1..h).step_by(2) {
+        for x in 0..w {
+            inside[x][y] = boundary[x][y] || inside[x][y + 1];
+        }
+    }
+    let count = |ps: &Vec<Coord>| {
+        ps.iter()
+            .filter(|p| inside[index(&xs, p.x as i64)][index(&ys, p.y as i64)])
+            .count() as i64
+    };
+    count(&input.saba) - count(&input.iwashi)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+    use rand_pcg::Pcg64Mcg;
+
+    use super::{calc_length, exact_score, is_simple, RectCounter};
+    use crate::{coord::Coord, input::Input};
+
+    #[test]
+    fn test_rect_counter() {
+        let points = vec![
+            Coord::new(0, 0),
+            Coord::new(5, 5),
+            Coord::new(5, 9),
+            Coord::new(10, 3),
+        ];
+        let counter = RectCounter::new(&points);
+        assert_eq!(counter.count(0, 10, 0, 10), 4);
+        assert_eq!(counter.count(5, 5, 0, 8), 1);
+        assert_eq!(counter.count(1, 10, 4, 9), 2);
+        assert_eq!(counter.count(6, 4, 0, 10), 0);
+    }
+
+    #[test]
+    fn test_is_simple() {
+        let square = vec![(0, 0), (0, 10), (10, 10), (10, 0)];
+        assert!(is_simple(&square));
+        assert_eq!(calc_length(&square), 40);
+        // 自己交差
+        let cross = vec![
+            (0, 0),
+            (0, 10),
+            (10, 10),
+            (10, 5),
+            (-5, 5),
+            (-5, 2),
+            (5, 2),
+            (5, 0),
+        ];
+        assert!(!is_simple(&cross));
+    }
+
+    #[test]
+    fn test_exact_score() {
+        // 各点で半直線と辺の交差を数える素朴な実装と比べる
+        let naive = |vs: &Vec<(i64, i64)>, ps: &Vec<Coord>| {
+            let contains = |p: &Coord| {
+                let (x, y) = (p.x as i64, p.y as i64);
+                let mut inside = false;
+                for i in 0..vs.len() {
+                    let (a, b) = (vs[i], vs[(i + 1) % vs.len()]);
+                    if a.0.min(b.0) <= x
+                        && x <= a.0.max(b.0)
+                        && a.1.min(b.1) <= y
+                        && y <= a.1.max(b.1)
+                    {
+                        return true;
+                    }
+                    if a.0 == b.0 && a.0 > x && (a.1 > y) != (b.1 > y) {
+                        inside = !inside;
+                    }
+                }
+                inside
+            };
+            ps.iter().filter(|p| contains(p)).count() as i64
+        };
+        let mut rng = Pcg64Mcg::new(0);
+        for _ in 0..200 {
+            // 自己交差も含む、水平と垂直の辺が交互に並ぶ多角形
+            let k = rng.gen_range(2..=6);
+            let xs = (0..k).map(|_| rng.gen_range(0..10)).collect::<Vec<i64>>();
+            let ys = (0..k).map(|_| rng.gen_range(0..10)).collect::<Vec<i64>>();
+            let mut vs = vec![];
+            for i in 0..k {
+                vs.push((xs[i], ys[i]));
+                vs.push((xs[(i + 1) % k], ys[i]));
+            }
+            let points = |rng: &mut Pcg64Mcg| {
+                (0..30)
+                    .map(|_| Coord::new(rng.gen_range(0..11), rng.gen_range(0..11)))
+                    .collect::<Vec<_>>()
+            };
+            let input = Input {
+                N: 30,
+                size: 10,
+                cmin: 0,
+                cmax: 10,
+                saba: points(&mut rng),
+                iwashi: points(&mut rng),
+            };
+            assert_eq!(
+                exact_score(&vs, &input),
+                naive(&vs, &input.saba) - naive(&vs, &input.iwashi),
+                "{:?}",
+                vs
+            );
+        }
+    }
+}
@@ -4,94 +4,141 @@ use rand_pcg::Pcg64Mcg;
 use crate::{
     common::{get_mask9, get_time},
     coord::{Coord, DXY4},
+    grid::Axis,
     input::Input,
+    polygon::is_vertex,
+    trajectory::Trajectory,
 };
 
+const LENGTH_LIMIT: i64 = 4e5 as i64;
+const VERTEX_LIMIT: usize = 1000;
+
 pub struct State {
     pub grid_num: usize,
-    pub dl: i64,
+    pub xs: Axis,
+    pub ys: Axis,
     pub grid: Vec<Vec<bool>>,
     pub length: i64,
+    pub vertex_num: usize,
     pub score: i64,
     pub score_map: Vec<Vec<i64>>,
-    pub best_dl: i64,
+    pub best_xs: Axis,
+    pub best_ys: Axis,
     pub best_length: i64,
     pub best_grid: Vec<Vec<bool>>,
     pub best_score: i64,
+    pub trajectory: Trajectory,
 }
 
 impl State {
     pub fn new(grid_num: usize, input: &Input) -> Self {
-        assert!(input.size % grid_num == 0);
-        let grid = vec![vec![true; grid_num]; grid_num];
-        let dl = input.size / grid_num;
-        let score_map = calc_score_map(grid_num, input);
+        let (xs, ys) = make_axes(grid_num, input);
+        let grid = vec![vec![true; ys.num()]; xs.num()];
+        let score_map = calc_score_map(&xs, &ys, input);
 
         Self {
             grid_num,
-            dl: dl as i64,
+            xs: xs.clone(),
+            ys: ys.clone(),
             grid,
             length: input.size as i64 * 4,
+            vertex_num: 4,
             score: 0,
             score_map,
-            best_dl: dl as i64,
+            best_xs: xs,
+            best_ys: ys,
             best_length: input.size as i64 * 4,
             best_grid: vec![],
             best_score: 0,
+            trajectory: Trajectory::new(0.01),
         }
     }
     pub fn annealing(&mut self, rng: &mut Pcg64Mcg, connect9: &Vec<bool>, tle: f64) {
         let mut iter = 0;
-        let T0 = 1e4 / self.grid_num as f64 / self.grid_num as f64;
+        let T0 = 1e4 / self.xs.num() as f64 / self.ys.num() as f64;
         let T1 = T0 * 0.1;
         while get_time() < tle {
             // ランダムにグリッドを選んで、エッジに隣接していれば、追加または削除を行う
-            let x = rng.gen_range(0..self.grid_num);
-            let y = rng.gen_range(0..self.grid_num);
+            let x = rng.gen_range(0..self.xs.num());
+            let y = rng.gen_range(0..self.ys.num());
             let pos = Coord::new(x, y);
             if !self.is_next_to_edge(pos) {
                 continue;
             }
-            let added = !self.grid[x][y];
-            if !self.legal_action(pos, added, connect9) {
+            // 1セルの反転、長方形の反転、境界に沿った行・列の反転
+            let r = rng.gen_range(0..10);
+            let (cells, diff_length, diff_vertex, diff_score) = if r < 7 {
+                let added = !self.grid[x][y];
+                if !self.legal_action(pos, added, connect9) {
+                    continue;
+                }
+                let diff_length = self.calc_diff_length(pos);
+                let diff_vertex = self.calc_diff_vertex(pos);
+                let diff_score = self.calc_diff_score(pos);
+                (vec![pos], diff_length, diff_vertex, diff_score)
+            } else {
+                let cells = if r < 9 {
+                    self.rect_cells(pos, rng)
+                } else {
+                    self.segment_cells(pos, rng)
+                };
+                if cells.len() <= 1 {
+                    continue;
+                }
+                let Some((diff_length, diff_vertex, diff_score)) = self.calc_block_diff(&cells)
+                else {
+                    continue;
+                };
+                (cells, diff_length, diff_vertex, diff_score)
+            };
+            if self.length + diff_length > LENGTH_LIMIT {
                 continue;
             }
-            let diff_length = self.calc_diff_length(pos);
-            if self.length + diff_length > 4e5 as i64 {
+            if self.vertex_num as i64 + diff_vertex > VERTEX_LIMIT as i64 {
                 continue;
             }
-            let diff_score = self.calc_diff_score(pos);
-            let temp = T0 + (T1 - T0) * get_time() / tle;
-            if diff_score >= 0 || rng.gen_bool((diff_score as f64 / temp).exp()) {
+            let time = get_time();
+            let temp = T0 + (T1 - T0) * time / tle;
+            let accepted = diff_score >= 0 || rng.gen_bool((diff_score as f64 / temp).exp());
+            if accepted {
                 iter += 1;
                 self.length += diff_length;
+                self.vertex_num = (self.vertex_num as i64 + diff_vertex) as usize;
                 self.score += diff_score;
-                self.grid[pos.x][pos.y] ^= true;
+                for c in cells.iter() {
+                    self.grid[c.x][c.y] ^= true;
+                }
                 if self.score > self.best_score {
                     self.best_score = self.score;
                     self.best_grid = self.grid.clone();
                     self.best_length = self.length;
-                    self.best_dl = self.dl;
-                    #[cfg(feature = "local")]
-                    eprintln!(
-                        "grid num: {} score: {} elapsed: {}",
-                        self.grid_num,
-                        self.best_score,
-                        get_time()
-                    );
+                    self.best_xs = self.xs.clone();
+                    self.best_ys = self.ys.clone();
                 }
             }
+            self.trajectory.push(
+                time,
+                self.grid_num,
+                self.best_score,
+                self.score,
+                temp,
+                accepted,
+            );
         }
         eprintln!("Iter = {}", iter);
     }
+    /// 圧縮で軸のセルが grid_num 個に満たないことがあるので、範囲は xs, ys のセル数で判定する
+    fn in_grid(&self, pos: Coord) -> bool {
+        pos.in_map(self.xs.num(), self.ys.num())
+    }
     pub fn is_next_to_edge(&self, pos: Coord) -> bool {
         // エッジに隣接しているグリッドかどうか
         for dxy in &DXY4 {
             let nxt = pos + *dxy;
-            if self.grid[pos.x][pos.y] && !nxt.in_map(self.grid_num) {
+            if self.grid[pos.x][pos.y] && !self.in_grid(nxt) {
                 return true;
             }
-            if !nxt.in_map(self.grid_num) {
+            if !self.in_grid(nxt) {
                 continue;
             }
             if self.grid[pos.x][pos.y] != self.grid[nxt.x][nxt.y] {
@@ -111,29 +158,217 @@ impl State {
     }
     pub fn calc_diff_length(&self, pos: Coord) -> i64 {
         // 多角形の長さの差分を計算
-        // 隣接しているグリッドが同じ状態の数を数える(外周の場合は網ではないとする)
-        // 1つだけ隣接している場合は-2、3つ隣接している場合は+2
-        let mut cnt = 0;
-        for dxy in &DXY4 {
+        // 隣接しているグリッドと状態が異なる辺は反転後に消え、同じ辺は反転後に現れる
+        // 上下の辺の長さはセルの幅、左右の辺の長さはセルの高さ(外周の場合は網ではないとする)
+        let w = self.xs.width(pos.x) as i64;
+        let h = self.ys.width(pos.y) as i64;
+        let mut diff = 0;
+        for (i, dxy) in DXY4.iter().enumerate() {
             let nxt = pos + *dxy;
-            if !self.grid[pos.x][pos.y] && !nxt.in_map(self.grid_num) {
-                cnt += 1;
-                continue;
+            let nxt_in = self.in_grid(nxt) && self.grid[nxt.x][nxt.y];
+            let len = if i % 2 == 0 { w } else { h };
+            if self.grid[pos.x][pos.y] != nxt_in {
+                diff -= len;
+            } else {
+                diff += len;
             }
-            if !nxt.in_map(self.grid_num) {
-                continue;
-            }
-            if self.grid[pos.x][pos.y] == self.grid[nxt.x][nxt.y] {
-                cnt += 1;
+        }
+        diff
+    }
+    pub fn calc_diff_vertex(&mut self, pos: Coord) -> i64 {
+        // 頂点数の差分を計算
+        // 反転で変わりうるのはセルの四隅のみ
+        let corners = [
+            pos,
+            Coord::new(pos.x + 1, pos.y),
+            Coord::new(pos.x, pos.y + 1),
+            Coord::new(pos.x + 1, pos.y + 1),
+        ];
+        let count =
+            |grid: &Vec<Vec<bool>>| corners.iter().filter(|c| is_vertex(**c, grid)).count() as i64;
+        let before = count(&self.grid);
+        self.grid[pos.x][pos.y] ^= true;
+        let after = count(&self.grid);
+        self.grid[pos.x][pos.y] ^= true;
+        after - before
+    }
+    /// まとめて反転するセルの一辺の最大値
+    fn block_size(&self) -> usize {
+        (self.grid_num / 25).max(2)
+    }
+    /// pos を角に含み、pos と同じ状態のセルだけからなる長方形
+    pub fn rect_cells(&self, pos: Coord, rng: &mut Pcg64Mcg) -> Vec<Coord> {
+        let k = self.block_size();
+        let w = rng.gen_range(1..=k);
+        let h = rng.gen_range(1..=k);
+        let x0 = if rng.gen_bool(0.5) {
+            pos.x
+        } else {
+            (pos.x + 1).saturating_sub(w)
+        };
+        let y0 = if rng.gen_bool(0.5) {
+            pos.y
+        } else {
+            (pos.y + 1).saturating_sub(h)
+        };
+        let state = self.grid[pos.x][pos.y];
+        let mut cells = vec![];
+        for x in x0..(x0 + w).min(self.xs.num()) {
+            for y in y0..(y0 + h).min(self.ys.num()) {
+                if self.grid[x][y] != state {
+                    return vec![];
+                }
+                cells.push(Coord::new(x, y));
             }
         }
-        if cnt == 1 {
-            self.dl * (-2)
-        } else if cnt == 3 {
-            self.dl * 2
+        cells
+    }
+    /// pos から縦または横に、pos と同じ状態でエッジに隣接しているセルを伸ばした線分
+    pub fn segment_cells(&self, pos: Coord, rng: &mut Pcg64Mcg) -> Vec<Coord> {
+        let len = rng.gen_range(2..=2 * self.block_size());
+        let state = self.grid[pos.x][pos.y];
+        let (d0, d1) = if rng.gen_bool(0.5) {
+            (DXY4[0], DXY4[2])
         } else {
-            0
+            (DXY4[1], DXY4[3])
+        };
+        let mut cells = vec![pos];
+        for d in [d0, d1] {
+            let mut cur = pos;
+            while cells.len() < len {
+                cur = cur + d;
+                if !self.in_grid(cur)
+                    || self.grid[cur.x][cur.y] != state
+                    || !self.is_next_to_edge(cur)
+                {
+                    break;
+                }
+                cells.push(cur);
+            }
+        }
+        cells
+    }
+    /// 同じ状態のセルをまとめて反転したときの (長さ, 頂点数, スコア) の差分
+    /// 周囲1マスを含めた範囲で、網の内側と外側がそれぞれ連結でなければ None
+    pub fn calc_block_diff(&mut self, cells: &Vec<Coord>) -> Option<(i64, i64, i64)> {
+        let x0 = cells.iter().map(|c| c.x).min().unwrap();
+        let x1 = cells.iter().map(|c| c.x).max().unwrap();
+        let y0 = cells.iter().map(|c| c.y).min().unwrap();
+        let y1 = cells.iter().map(|c| c.y).max().unwrap();
+        let count_vertex = |grid: &Vec<Vec<bool>>| {
+            let mut cnt = 0;
+            for x in x0..=x1 + 1 {
+                for y in y0..=y1 + 1 {
+                    if is_vertex(Coord::new(x, y), grid) {
+                        cnt += 1;
+                    }
+                }
+            }
+            cnt
+        };
+        let length_before = self.cells_boundary_length(cells);
+        let vertex_before = count_vertex(&self.grid);
+        let diff_score = cells.iter().map(|&c| self.calc_diff_score(c)).sum();
+        for c in cells.iter() {
+            self.grid[c.x][c.y] ^= true;
+        }
+        let connected = self.is_locally_connected(x0, x1, y0, y1);
+        let length_after = self.cells_boundary_length(cells);
+        let vertex_after = count_vertex(&self.grid);
+        for c in cells.iter() {
+            self.grid[c.x][c.y] ^= true;
+        }
+        if !connected {
+            return None;
+        }
+        Some((
+            length_after - length_before,
+            vertex_after - vertex_before,
+            diff_score,
+        ))
+    }
+    // セルの各辺のうち、隣と状態が異なる辺の長さの和 (セル同士で共有する辺は2回数える)
+    fn cells_boundary_length(&self, cells: &Vec<Coord>) -> i64 {
+        let mut ret = 0;
+        for &pos in cells.iter() {
+            for (i, dxy) in DXY4.iter().enumerate() {
+                let nxt = pos + *dxy;
+                let nxt_in = self.in_grid(nxt) && self.grid[nxt.x][nxt.y];
+                if self.grid[pos.x][pos.y] != nxt_in {
+                    ret += if i % 2 == 0 {
+                        self.xs.width(pos.x)
+                    } else {
+                        self.ys.width(pos.y)
+                    } as i64;
+                }
+            }
+        }
+        ret
+    }
+    // [x0 - 1, x1 + 1] × [y0 - 1, y1 + 1] の範囲で、網の内側と外側がそれぞれ1つの連結成分で、範囲の縁に届くか
+    // 縁に届かない成分は範囲の外とつながらない (穴や島になる)
+    // 範囲外のセルは外側で、外周を通して互いに連結とみなす
+    fn is_locally_connected(&self, x0: usize, x1: usize, y0: usize, y1: usize) -> bool {
+        let lx = x0 as i64 - 1;
+        let ly = y0 as i64 - 1;
+        let w = x1 - x0 + 3;
+        let h = y1 - y0 + 3;
+        let get = |i: usize, j: usize| {
+            let (x, y) = (lx + i as i64, ly + j as i64);
+            if x < 0 || y < 0 || x >= self.xs.num() as i64 || y >= self.ys.num() as i64 {
+                None
+            } else {
+                Some(self.grid[x as usize][y as usize])
+            }
+        };
+        // 範囲外のセルは1つの成分としてまとめて訪問する
+        let outside = (0..w)
+            .flat_map(|i| (0..h).map(move |j| (i, j)))
+            .filter(|&(i, j)| get(i, j).is_none())
+            .collect::<Vec<_>>();
+        for state in [true, false] {
+            let mut visited = vec![vec![false; h]; w];
+            let mut components = 0;
+            for si in 0..w {
+                for sj in 0..h {
+                    if visited[si][sj] || get(si, sj).unwrap_or(false) != state {
+                        continue;
+                    }
+                    components += 1;
+                    let mut stack = vec![(si, sj)];
+                    visited[si][sj] = true;
+                    while let Some((i, j)) = stack.pop() {
+                        if get(i, j).is_none() {
+                            for &(oi, oj) in outside.iter() {
+                                if !visited[oi][oj] {
+                                    visited[oi][oj] = true;
+                                    stack.push((oi, oj));
+                                }
+                            }
+                        }
+                        for (di, dj) in [(0, 1), (1, 0), (0, !0), (!0, 0)] {
+                            let (ni, nj) = (i.wrapping_add(di), j.wrapping_add(dj));
+                            if ni >= w || nj >= h || visited[ni][nj] {
+                                continue;
+                            }
+                            if get(ni, nj).unwrap_or(false) != state {
+                                continue;
+                            }
+                            visited[ni][nj] = true;
+                            stack.push((ni, nj));
+                        }
+                    }
+                }
+            }
+            let on_ring = |i: usize, j: usize| i == 0 || j == 0 || i == w - 1 || j == h - 1;
+            let touches_ring = (0..w)
+                .flat_map(|i| (0..h).map(move |j| (i, j)))
+                .any(|(i, j)| on_ring(i, j) && get(i, j).unwrap_or(false) == state);
+            if components != 1 || !touches_ring {
+                return false;
+            }
         }
+        true
     }
     pub fn calc_diff_score(&self, pos: Coord) -> i64 {
         // スコアの差分を計算
@@ -145,14 +380,15 @@ impl State {
     }
     pub fn to_next_grid(&mut self, grid_num: usize, input: &Input) {
         // 次のグリッド分割に移行
-        let dl = input.size / grid_num;
-        let score_map = calc_score_map(grid_num, input);
-        let mut grid = vec![vec![false; grid_num]; grid_num];
+        // 新しいセルの中心を含む、元のセルの状態を引き継ぐ
+        let (xs, ys) = make_axes(grid_num, input);
+        let score_map = calc_score_map(&xs, &ys, input);
+        let mut grid = vec![vec![false; ys.num()]; xs.num()];
         let mut score = 0;
-        for x in 0..grid_num {
-            for y in 0..grid_num {
-                let bx = x * dl / (self.dl as usize);
-                let by = y * dl / (self.dl as usize);
+        for x in 0..xs.num() {
+            for y in 0..ys.num() {
+                let bx = self.xs.index(xs.center(x));
+                let by = self.ys.index(ys.center(y));
                 if self.grid[bx][by] {
                     grid[x][y] = true;
                     score += score_map[x][y];
@@ -160,22 +396,32 @@ impl State {
             }
         }
         let mut length = 0;
-        for x in 0..grid_num {
-            for y in 0..grid_num {
+        for x in 0..xs.num() {
+            for y in 0..ys.num() {
                 if !grid[x][y] {
                     continue;
                 }
                 let pos = Coord::new(x, y);
-                for dxy in &DXY4 {
+                for (i, dxy) in DXY4.iter().enumerate() {
                     let nxt = pos + *dxy;
-                    if !nxt.in_map(grid_num) || !grid[nxt.x][nxt.y] {
-                        length += dl;
+                    if !nxt.in_map(xs.num(), ys.num()) || !grid[nxt.x][nxt.y] {
+                        length += if i % 2 == 0 { xs.width(x) } else { ys.width(y) };
                     }
                 }
             }
         }
+        let mut vertex_num = 0;
+        for x in 0..=xs.num() {
+            for y in 0..=ys.num() {
+                if is_vertex(Coord::new(x, y), &grid) {
+                    vertex_num += 1;
+                }
+            }
+        }
         self.grid = grid;
-        self.dl = dl as i64;
+        self.vertex_num = vertex_num;
+        self.xs = xs;
+        self.ys = ys;
         self.score_map = score_map;
         self.grid_num = grid_num;
         self.score = score;
@@ -183,18 +429,177 @@ impl State {
     }
 }
 
-fn calc_score_map(grid_num: usize, input: &Input) -> Vec<Vec<i64>> {
-    let dl = input.size / grid_num;
-    let mut score_map = vec![vec![0; grid_num]; grid_num];
+/// 魚の座標を圧縮した、一辺 grid_num 個以下のグリッド
+fn make_axes(grid_num: usize, input: &Input) -> (Axis, Axis) {
+    let fish = || input.saba.iter().chain(input.iwashi.iter());
+    let xs = Axis::compressed(fish().map(|p| p.x).collect(), input.size, grid_num);
+    let ys = Axis::compressed(fish().map(|p| p.y).collect(), input.size, grid_num);
+    (xs, ys)
+}
+
+fn calc_score_map(xs: &Axis, ys: &Axis, input: &Input) -> Vec<Vec<i64>> {
+    let mut score_map = vec![vec![0; ys.num()]; xs.num()];
     for pos in input.saba.iter() {
-        let x = pos.x / dl;
-        let y = pos.y / dl;
-        score_map[x][y] += 1;
+        score_map[xs.index(pos.x)][ys.index(pos.y)] += 1;
     }
     for pos in input.iwashi.iter() {
-        let x = pos.x / dl;
-        let y = pos.y / dl;
-        score_map[x][y] -= 1;
+        score_map[xs.index(pos.x)][ys.index(pos.y)] -= 1;
     }
     score_map
 }
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+    use rand_pcg::Pcg64Mcg;
+
+    use super::State;
+    use crate::{
+        coord::{Coord, DXY4},
+        input::Input,
+        polygon::is_vertex,
+    };
+
+    fn small_state(rng: &mut Pcg64Mcg) -> State {
+        let size = 100;
+        let mut points = || {
+            (0..20)
+                .map(|_| Coord::new(rng.gen_range(0..=size), rng.gen_range(0..=size)))
+                .collect::<Vec<_>>()
+        };
+        let input = Input {
+            N: 20,
+            size,
+            cmin: 0,
+            cmax: size,
+            saba: points(),
+            iwashi: points(),
+        };
+        State::new(8, &input)
+    }
+
+    // (周長, 頂点数, スコア) をグリッドから計算し直す
+    fn from_scratch(state: &State) -> (i64, i64, i64) {
+        let (w, h) = (state.xs.num(), state.ys.num());
+        let (mut length, mut vertex, mut score) = (0, 0, 0);
+        for x in 0..w {
+            for y in 0..h {
+                if !state.grid[x][y] {
+                    continue;
+                }
+                score += state.score_map[x][y];
+                for (i, dxy) in DXY4.iter().enumerate() {
+                    let nxt = Coord::new(x, y) + *dxy;
+                    if !nxt.in_map(w, h) || !state.grid[nxt.x][nxt.y] {
+                        length += if i % 2 == 0 {
+                            state.xs.width(x)
+                        } else {
+                            state.ys.width(y)
+                        } as i64;
+                    }
+                }
+            }
+        }
+        for x in 0..=w {
+            for y in 0..=h {
+                vertex += is_vertex(Coord::new(x, y), &state.grid) as i64;
+            }
+        }
+        (length, vertex, score)
+    }
+
+    // 網の内側と、グリッドの外を含めた外側がそれぞれ連結か
+    fn is_connected(state: &State) -> bool {
+        let (w, h) = (state.xs.num() + 2, state.ys.num() + 2);
+        let get = |i: usize, j: usize| {
+            i > 0 && j > 0 && i < w - 1 && j < h - 1 && state.grid[i - 1][j - 1]
+        };
+        [true, false].iter().all(|&s| {
+            let cells = (0..w)
+                .flat_map(|i| (0..h).map(move |j| (i, j)))
+                .filter(|&(i, j)| get(i, j) == s)
+                .collect::<Vec<_>>();
+            let mut visited = vec![vec![false; h]; w];
+            let mut stack = vec![cells[0]];
+            visited[cells[0].0][cells[0].1] = true;
+            let mut cnt = 0;
+            while let Some((i, j)) = stack.pop() {
+                cnt += 1;
+                for (di, dj) in [(0, 1), (1, 0), (0, !0), (!0, 0)] {
+                    let (ni, nj) = (i.wrapping_add(di), j.wrapping_add(dj));
+                    if ni < w && nj < h && !visited[ni][nj] && get(ni, nj) == s {
+                        visited[ni][nj] = true;
+                        stack.push((ni, nj));
+                    }
+                }
+            }
+            cnt == cells.len()
+        })
+    }
+
+    #[test]
+    fn test_block_flip() {
+        let mut rng = Pcg64Mcg::new(0);
+        let mut state = small_state(&mut rng);
+        let (w, h) = (state.xs.num(), state.ys.num());
+        state.score = from_scratch(&state).2;
+        let mut accepted = 0;
+        for _ in 0..3000 {
+            let pos = Coord::new(rng.gen_range(0..w), rng.gen_range(0..h));
+            if !state.is_next_to_edge(pos) {
+                continue;
+            }
+            let cells = if rng.gen_bool(0.5) {
+                state.rect_cells(pos, &mut rng)
+            } else {
+                state.segment_cells(pos, &mut rng)
+            };
+            if cells.len() <= 1 {
+                continue;
+            }
+            let s = state.grid[pos.x][pos.y];
+            assert!(cells.contains(&pos));
+            assert!(cells.iter().all(|c| state.grid[c.x][c.y] == s));
+            let Some((diff_length, diff_vertex, diff_score)) = state.calc_block_diff(&cells) else {
+                continue;
+            };
+            let before = from_scratch(&state);
+            for c in cells.iter() {
+                state.grid[c.x][c.y] ^= true;
+            }
+            let after = from_scratch(&state);
+            assert_eq!(
+                (diff_length, diff_vertex, diff_score),
+                (after.0 - before.0, after.1 - before.1, after.2 - before.2)
+            );
+            assert!(is_connected(&state));
+            accepted += 1;
+        }
+        assert!(accepted > 100);
+    }
+
+    #[test]
+    fn test_block_flip_disconnect() {
+        let mut rng = Pcg64Mcg::new(0);
+        let mut state = small_state(&mut rng);
+        // 全体が網の内側のとき、中央の 2x2 を外すと穴ができる
+        let hole = vec![
+            Coord::new(3, 3),
+            Coord::new(3, 4),
+            Coord::new(4, 3),
+            Coord::new(4, 4),
+        ];
+        assert!(state.calc_block_diff(&hole).is_none());
+        // 横一列の網を途中で切ると内側が分かれる
+        for x in 0..state.xs.num() {
+            for y in 0..state.ys.num() {
+                state.grid[x][y] = y == 2;
+            }
+        }
+        let cut = vec![Coord::new(3, 2), Coord::new(4, 2)];
+        assert!(state.calc_block_diff(&cut).is_none());
+        // 端を削るのは連結のまま
+        let end = vec![Coord::new(0, 2), Coord::new(1, 2)];
+        assert!(state.calc_block_diff(&end).is_some());
+    }
+}
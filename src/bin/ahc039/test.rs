@@ -8,11 +8,14 @@ mod tests {
     use std::process::{Command, Stdio};
     use std::thread;
 
+    use crate::trajectory;
+
     struct Result {
         test_number: String,
         score: usize,
         elapsed_time: f64,
         length: usize,
+        converged: f64,
         is_ac: bool,
         is_tle: bool,
     }
@@ -26,8 +29,13 @@ mod tests {
             }
             write!(
                 f,
-                "{},{},{},{},{}",
-                self.test_number, self.length, self.score, self.elapsed_time, result
+                "{},{},{},{},{:.3},{}",
+                self.test_number,
+                self.length,
+                self.score,
+                self.elapsed_time,
+                self.converged,
+                result
             )?;
             Ok(())
         }
@@ -86,10 +94,14 @@ mod tests {
             .next()
             .unwrap();
 
+        // スコア推移の出力先
+        let trajectory_file = format!("tools/{}/out/{}_trajectory.csv", exe_filename, test_number);
+
         // run + visualize
         // exp: makers run ahc038 0000
         let run_output = Command::new("makers")
             .args(["run", exe_filename, test_number.as_str()])
+            .env("TRAJECTORY", &trajectory_file)
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .output()
@@ -118,8 +130,19 @@ mod tests {
             }
         }
 
+        // スコア推移をプロットし、最良スコアに到達した時刻を取得
+        let records = trajectory::load(&trajectory_file);
+        let converged = trajectory::converged_time(&records);
+        if records.len() > 0 {
+            std::fs::write(
+                trajectory_file.replace(".csv", ".svg"),
+                trajectory::plot(&records, TLE),
+            )
+            .unwrap();
+        }
+
         println!(
-            "{}: length={}, score={}, elapsed={}",
+            "{}: length={}, score={}, elapsed={}, converged={:.3}",
             if vis_score == 0 {
                 test_number.to_string().red()
             } else {
@@ -131,7 +154,8 @@ mod tests {
                 elapsed_time.to_string().yellow()
             } else {
                 elapsed_time.to_string().white()
-            }
+            },
+            converged
         );
 
         Result {
@@ -139,6 +163,7 @@ mod tests {
             score: vis_score,
             elapsed_time,
             length,
+            converged,
             is_ac: vis_score > 0,
             is_tle: elapsed_time > TLE,
         }
@@ -150,7 +175,7 @@ mod tests {
         let test_case_num = 100;
         let results = cocurrent(job_num, run, (0..test_case_num).collect_vec());
         let mut file = File::create("results.csv").unwrap();
-        writeln!(file, "{}", "length,score,elapsed,result").unwrap();
+        writeln!(file, "{}", "length,score,elapsed,converged,result").unwrap();
         let mut score_sum = 0;
         let mut wa_cnt = 0;
         let mut tle_cnt = 0;
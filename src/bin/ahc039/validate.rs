@@ -0,0 +1,192 @@
+// tools/ahc039 の parse_output, compute_score_details と同じ規則で出力を検証する
+
+use crate::{coord::Coord, input::Input, polygon_opt::PolygonState};
+
+const SIZE: i64 = 1e5 as i64;
+const LENGTH_LIMIT: i64 = 4e5 as i64;
+const VERTEX_LIMIT: usize = 1000;
+
+/// 規則を満たしていれば周長を返す
+pub fn validate(out: &Vec<(i64, i64)>) -> Result<i64, String> {
+    if out.len() < 4 || out.len() > VERTEX_LIMIT {
+        return Err(format!(
+            "The number of vertices is out of range: {}.",
+            out.len()
+        ));
+    }
+    for &(x, y) in out.iter() {
+        if x < 0 || x > SIZE || y < 0 || y > SIZE {
+            return Err(format!("The vertex ({}, {}) is out of range.", x, y));
+        }
+    }
+    let mut len = 0;
+    for i in 0..out.len() {
+        let p = out[i];
+        let q = out[(i + 1) % out.len()];
+        let r = out[(i + 2) % out.len()];
+        if p == q {
+            return Err("Two consecutive vertices share the same coordinates.".to_owned());
+        } else if p.0 == q.0 {
+            len += (p.1 - q.1).abs();
+            if q.0 == r.0 && (p.1 - q.1) * (r.1 - q.1) > 0 {
+                return Err("The polygon is self-intersecting.".to_owned());
+            }
+        } else if p.1 == q.1 {
+            len += (p.0 - q.0).abs();
+            if q.1 == r.1 && (p.0 - q.0) * (r.0 - q.0) > 0 {
+                return Err("The polygon is self-intersecting.".to_owned());
+            }
+        } else {
+            return Err(format!("The {}-th edge is not parallel to the axes.", i));
+        }
+    }
+    for i in 0..out.len() {
+        for j in 2..out.len() - 1 {
+            let j = (i + j) % out.len();
+            let p1 = out[i];
+            let p2 = out[(i + 1) % out.len()];
+            let q1 = out[j];
+            let q2 = out[(j + 1) % out.len()];
+            if p1.0.min(p2.0).max(q1.0.min(q2.0)) <= p1.0.max(p2.0).min(q1.0.max(q2.0))
+                && p1.1.min(p2.1).max(q1.1.min(q2.1)) <= p1.1.max(p2.1).min(q1.1.max(q2.1))
+            {
+                return Err("The polygon is self-intersecting.".to_owned());
+            }
+        }
+    }
+    if len > LENGTH_LIMIT {
+        return Err(format!("The length is too long: {}.", len));
+    }
+    Ok(len)
+}
+
+/// 頂点数・周長の制限を超えていれば、スコアの減少が小さい段から潰す
+/// 修正できなければ全体を囲む正方形を返す
+pub fn make_valid(polygon: Vec<(i64, i64)>, input: &Input) -> Vec<(i64, i64)> {
+    let Err(err) = validate(&polygon) else {
+        return polygon;
+    };
+    eprintln!("Invalid polygon: {}", err);
+    let coords = polygon
+        .iter()
+        .map(|&(x, y)| Coord::new(x as usize, y as usize))
+        .collect();
+    let mut repaired = PolygonState::new(&coords, input);
+    if repaired.repair() && validate(&repaired.vs).is_ok() {
+        repaired.vs
+    } else {
+        let size = input.size as i64;
+        vec![(0, 0), (0, size), (size, size), (size, 0)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{make_valid, validate, LENGTH_LIMIT, SIZE, VERTEX_LIMIT};
+    use crate::{coord::Coord, input::Input, polygon_opt::exact_score};
+
+    fn input(saba: Vec<Coord>, iwashi: Vec<Coord>) -> Input {
+        Input {
+            N: saba.len(),
+            size: SIZE as usize,
+            cmin: 2e4 as usize,
+            cmax: 8e4 as usize,
+            saba,
+            iwashi,
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        let square = vec![(0, 0), (0, 100), (100, 100), (100, 0)];
+        assert_eq!(validate(&square), Ok(400));
+        let spike = vec![(0, 0), (0, 100), (0, 50), (100, 50), (100, 0)];
+        assert!(validate(&spike).is_err());
+        let long = vec![(0, 0), (0, 100000), (100000, 100000), (100000, 0)];
+        assert!(validate(&long).is_ok());
+        let mut stairs = vec![(0, 0)];
+        for k in 0..600 {
+            stairs.push((k, k + 1));
+            stairs.push((k + 1, k + 1));
+        }
+        stairs.push((600, 0));
+        assert!(validate(&stairs).is_err());
+    }
+
+    #[test]
+    fn test_repair_vertex_num() {
+        // 1段 100 の階段で頂点数だけが多すぎる
+        let mut stairs = vec![(0, 0)];
+        for k in 0..600 {
+            stairs.push((k * 100, (k + 1) * 100));
+            stairs.push(((k + 1) * 100, (k + 1) * 100));
+        }
+        stairs.push((60000, 0));
+        assert!(validate(&stairs)
+            .unwrap_err()
+            .starts_with("The number of vertices"));
+        // 階段の下にサバ、上にイワシがいる
+        let saba = (0..600)
+            .map(|k| Coord::new(k * 100 + 50, k * 100 + 10))
+            .collect();
+        let iwashi = (0..600)
+            .map(|k| Coord::new(k * 100 + 10, k * 100 + 150))
+            .collect();
+        let input = input(saba, iwashi);
+        assert_eq!(exact_score(&stairs, &input), 600);
+        let repaired = make_valid(stairs, &input);
+        assert!(validate(&repaired).is_ok());
+        assert!(repaired.len() <= VERTEX_LIMIT);
+        // 潰した 101 段の分だけサバを失うが、全体を囲む正方形 (スコア 0) にはならない
+        assert_eq!(exact_score(&repaired, &input), 499);
+    }
+
+    #[test]
+    fn test_repair_length() {
+        // 正方形から細長い切り込みを入れて周長だけが長すぎる
+        let comb = vec![
+            (0, 0),
+            (0, SIZE),
+            (SIZE, SIZE),
+            (SIZE, 0),
+            (60000, 0),
+            (60000, 90000),
+            (50000, 90000),
+            (50000, 0),
+        ];
+        assert!(validate(&comb).unwrap_err().starts_with("The length"));
+        // 切り込みの中にはイワシだけがいる
+        let saba = vec![Coord::new(10000, 10000), Coord::new(90000, 90000)];
+        let iwashi = vec![Coord::new(55000, 10000), Coord::new(55000, 80000)];
+        let input = input(saba, iwashi);
+        let repaired = make_valid(comb, &input);
+        let length = validate(&repaired).unwrap();
+        assert!(length <= LENGTH_LIMIT);
+        // 切り込みを閉じてイワシを入れずに、切り込みの横を削って周長を減らす
+        assert_eq!(exact_score(&repaired, &input), 2);
+    }
+
+    #[test]
+    fn test_repair_self_intersection() {
+        // 頂点数と周長は制限内だが、辺が交差していて repair では直せない
+        let cross = vec![
+            (0, 0),
+            (0, 100),
+            (100, 100),
+            (100, 50),
+            (-50, 50),
+            (-50, 20),
+            (50, 20),
+            (50, 0),
+        ];
+        let cross = cross
+            .into_iter()
+            .map(|(x, y)| (x + 100, y + 100))
+            .collect::<Vec<_>>();
+        assert!(validate(&cross)
+            .unwrap_err()
+            .starts_with("The polygon is self-intersecting"));
+        let repaired = make_valid(cross, &input(vec![], vec![]));
+        assert_eq!(repaired, vec![(0, 0), (0, SIZE), (SIZE, SIZE), (SIZE, 0)]);
+    }
+}
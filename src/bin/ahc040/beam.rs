@@ -4,6 +4,7 @@ use rustc_hash::FxHashSet;
 
 use crate::{
     input::Input,
+    select::pool_size,
     state::{Op, State},
 };
 
@@ -99,7 +100,7 @@ impl BeamSearch {
         input: &Input,
         _rng: &mut rand_pcg::Pcg64Mcg,
         is_ascending: bool,
-    ) -> Vec<Op> {
+    ) -> Vec<Vec<Op>> {
         let mut cands = Vec::<Cand>::new();
         let mut set = FxHashSet::default();
         for t in 0..depth {
@@ -127,29 +128,18 @@ impl BeamSearch {
             self.append_cands(input, &mut cands, _rng);
         }
 
+        // 評価値順の候補の配置 (ハッシュが同じものは除く)
         cands.sort_unstable_by_key(|a| a.eval_score);
-        for cand in cands.iter().take(input.T) {
+        set.clear();
+        let mut packings = vec![];
+        for cand in cands
+            .iter()
+            .filter(|cand| set.insert(cand.hash))
+            .take(pool_size(input.T))
+        {
             let mut ops = self.restore(cand.parent);
-            eprintln!(
-                "H2: {}, shelf_height: {}, shelf_margin: {}, S: {}",
-                self.nodes[cand.parent].state.H2,
-                self.nodes[cand.parent].state.shelf_height,
-                self.nodes[cand.parent].state.shelf_margin,
-                self.nodes[cand.parent].state.S - input.wh2[input.wh2.len()-1].0 as i64 *input.wh2[input.wh2.len()-1].1 as i64
-            );
             ops.push(cand.op.clone());
-            println!("{}", ops.len());
-            for Op {
-                p,
-                r,
-                d,
-                b,
-                pos: _,
-                row: _,
-            } in ops.iter()
-            {
-                println!("{} {} {} {}", p, if *r { 1 } else { 0 }, d, b,);
-            }
+            packings.push(ops);
         }
 
         let best = if is_ascending {
@@ -158,8 +148,6 @@ impl BeamSearch {
             cands.iter().max_by_key(|a| a.raw_score(input)).unwrap()
         };
         eprintln!("Ideal = {}", best.eval_score);
-        let mut ret = self.restore(best.parent);
-        ret.push(best.op.clone());
-        ret
+        packings
     }
 }
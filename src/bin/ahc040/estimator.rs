@@ -0,0 +1,230 @@
+// 箱の縦横の長さをガウス分布で推定する
+// 変数 2i が i 番目の箱の横、2i+1 が縦
+// 事前分布は wh2 を平均とする分散 sigma^2 の正規分布で、各測定は変数の和に分散 sigma^2 の誤差が乗ったもの
+// 事後分布の平均は重み付き最小二乗の解、共分散は正規方程式の係数行列の逆行列になる
+
+#[derive(Debug, Clone)]
+pub struct Estimator {
+    pub n: usize,
+    sigma: f64,
+    prior: Vec<f64>,
+    // 測定した変数の集合と測定値
+    pub measures: Vec<(Vec<usize>, f64)>,
+    pub mean: Vec<f64>,
+    pub cov: Vec<Vec<f64>>,
+}
+
+impl Estimator {
+    pub fn new(wh2: &Vec<(i64, i64)>, sigma: i64) -> Self {
+        let n = wh2.len();
+        let sigma = sigma.max(1) as f64;
+        let prior = wh2
+            .iter()
+            .flat_map(|&(w, h)| [w as f64, h as f64])
+            .collect::<Vec<_>>();
+        let mut cov = vec![vec![0.0; 2 * n]; 2 * n];
+        for i in 0..2 * n {
+            cov[i][i] = sigma * sigma;
+        }
+        Self {
+            n,
+            sigma,
+            mean: prior.clone(),
+            prior,
+            measures: vec![],
+            cov,
+        }
+    }
+    pub fn var_index(idx: usize, rotate: bool) -> usize {
+        2 * idx + rotate as usize
+    }
+    pub fn add(&mut self, vars: Vec<usize>, value: i64) {
+        self.measures.push((vars, value as f64));
+    }
+    /// 測定を1つ追加し、事後分布を逐次更新する
+    pub fn observe(&mut self, vars: Vec<usize>, value: i64) {
+        let m = 2 * self.n;
+        let mut v = vec![0.0; m];
+        for &j in vars.iter() {
+            for i in 0..m {
+                v[i] += self.cov[i][j];
+            }
+        }
+        let q = vars.iter().map(|&i| v[i]).sum::<f64>() + self.sigma * self.sigma;
+        let len = vars.iter().map(|&i| self.mean[i]).sum::<f64>();
+        for i in 0..m {
+            self.mean[i] += v[i] * (value as f64 - len) / q;
+            for j in 0..m {
+                self.cov[i][j] -= v[i] * v[j] / q;
+            }
+        }
+        self.add(vars, value);
+    }
+    /// 変数の和を測定したときの、事後分散の和の減少量の期待値
+    pub fn gain(&self, vars: &[usize]) -> f64 {
+        let m = 2 * self.n;
+        let mut v = vec![0.0; m];
+        for &j in vars.iter() {
+            for i in 0..m {
+                v[i] += self.cov[i][j];
+            }
+        }
+        let q = vars.iter().map(|&i| v[i]).sum::<f64>() + self.sigma * self.sigma;
+        v.iter().map(|x| x * x).sum::<f64>() / q
+    }
+    pub fn trace(&self) -> f64 {
+        (0..2 * self.n).map(|i| self.cov[i][i]).sum()
+    }
+    pub fn noise_var(&self) -> f64 {
+        self.sigma * self.sigma
+    }
+    /// 事前分布と全ての測定から事後分布を求め直す
+    pub fn solve(&mut self) {
+        let m = 2 * self.n;
+        let weight = 1.0 / (self.sigma * self.sigma);
+        let mut a = vec![vec![0.0; m]; m];
+        let mut b = vec![0.0; m];
+        for i in 0..m {
+            a[i][i] += weight;
+            b[i] += weight * self.prior[i];
+        }
+        for (vars, value) in self.measures.iter() {
+            for &i in vars.iter() {
+                for &j in vars.iter() {
+                    a[i][j] += weight;
+                }
+                b[i] += weight * value;
+            }
+        }
+        let l = cholesky(&a);
+        self.mean = cholesky_solve(&l, &b);
+        let mut e = vec![0.0; m];
+        for j in 0..m {
+            e[j] = 1.0;
+            let col = cholesky_solve(&l, &e);
+            for i in 0..m {
+                self.cov[i][j] = col[i];
+            }
+            e[j] = 0.0;
+        }
+    }
+    /// 事後分布の平均を整数に丸めたもの
+    pub fn wh(&self) -> Vec<(i64, i64)> {
+        (0..self.n)
+            .map(|i| {
+                (
+                    self.mean[2 * i].round() as i64,
+                    self.mean[2 * i + 1].round() as i64,
+                )
+            })
+            .collect()
+    }
+    /// 事後分布の標準偏差
+    pub fn std(&self) -> Vec<(f64, f64)> {
+        (0..self.n)
+            .map(|i| {
+                (
+                    self.cov[2 * i][2 * i].max(0.0).sqrt(),
+                    self.cov[2 * i + 1][2 * i + 1].max(0.0).sqrt(),
+                )
+            })
+            .collect()
+    }
+    /// 事後分布の平均での測定値の残差の二乗和
+    pub fn residual(&self) -> f64 {
+        self.measures
+            .iter()
+            .map(|(vars, value)| {
+                let len = vars.iter().map(|&i| self.mean[i]).sum::<f64>();
+                (len - value) * (len - value)
+            })
+            .sum()
+    }
+}
+
+// 正定値対称行列 a = l l^T となる下三角行列 l
+fn cholesky(a: &Vec<Vec<f64>>) -> Vec<Vec<f64>> {
+    let m = a.len();
+    let mut l = vec![vec![0.0; m]; m];
+    for i in 0..m {
+        for j in 0..=i {
+            let mut s = a[i][j];
+            for k in 0..j {
+                s -= l[i][k] * l[j][k];
+            }
+            if i == j {
+                l[i][i] = s.max(1e-300).sqrt();
+            } else {
+                l[i][j] = s / l[j][j];
+            }
+        }
+    }
+    l
+}
+
+// l l^T x = b を解く
+fn cholesky_solve(l: &Vec<Vec<f64>>, b: &Vec<f64>) -> Vec<f64> {
+    let m = l.len();
+    let mut y = b.clone();
+    for i in 0..m {
+        for k in 0..i {
+            y[i] -= l[i][k] * y[k];
+        }
+        y[i] /= l[i][i];
+    }
+    for i in (0..m).rev() {
+        for k in i + 1..m {
+            y[i] -= l[k][i] * y[k];
+        }
+        y[i] /= l[i][i];
+    }
+    y
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Estimator;
+
+    #[test]
+    fn test_solve() {
+        // 事前分布と測定値が1回ずつなら、平均は中点で分散は半分になる
+        let mut estimator = Estimator::new(&vec![(100, 200), (300, 400)], 10);
+        estimator.add(vec![Estimator::var_index(0, false)], 110);
+        estimator.solve();
+        assert!((estimator.mean[0] - 105.0).abs() < 1e-6);
+        assert!((estimator.cov[0][0] - 50.0).abs() < 1e-6);
+        assert!((estimator.mean[1] - 200.0).abs() < 1e-6);
+
+        // 和の測定は相関を持つ
+        estimator.add(
+            vec![
+                Estimator::var_index(0, true),
+                Estimator::var_index(1, false),
+            ],
+            530,
+        );
+        estimator.solve();
+        assert!((estimator.mean[1] - 210.0).abs() < 1e-6);
+        assert!((estimator.mean[2] - 310.0).abs() < 1e-6);
+        assert!(estimator.cov[1][2] < 0.0);
+
+        // 逐次更新でも同じ事後分布になる
+        let mut sequential = Estimator::new(&vec![(100, 200), (300, 400)], 10);
+        let gain = sequential.gain(&[Estimator::var_index(0, false)]);
+        sequential.observe(vec![Estimator::var_index(0, false)], 110);
+        assert!((gain - 50.0).abs() < 1e-6);
+        sequential.observe(
+            vec![
+                Estimator::var_index(0, true),
+                Estimator::var_index(1, false),
+            ],
+            530,
+        );
+        for i in 0..4 {
+            assert!((sequential.mean[i] - estimator.mean[i]).abs() < 1e-6);
+            for j in 0..4 {
+                assert!((sequential.cov[i][j] - estimator.cov[i][j]).abs() < 1e-6);
+            }
+        }
+    }
+}
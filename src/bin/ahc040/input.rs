@@ -1,10 +1,10 @@
 use proconio::input_interactive;
 
 use crate::hash::CalcHash;
-use crate::measure::measure;
+use crate::measure::{measure, Interactive};
 
-const MIN: i64 = 1e4 as i64;
-const MAX: i64 = 1e5 as i64;
+pub const MIN: i64 = 1e4 as i64;
+pub const MAX: i64 = 1e5 as i64;
 
 pub fn read_input() -> Input {
     input_interactive! {
@@ -26,9 +26,8 @@ pub fn read_input() -> Input {
     eprintln!("T = {}", T);
     eprintln!("sigma = {}", sigma);
 
-    let measure_num = (T as f64 * 0.7) as usize;
+    let (modified_wh, wh_std, measure_num) = measure(&mut Interactive, N, T, sigma, wh2.clone());
     T -= measure_num;
-    let modified_wh = measure(N, measure_num, sigma, wh2.clone());
 
     let mut clamped_wh = vec![];
     for (w, h) in modified_wh {
@@ -69,6 +68,7 @@ pub fn read_input() -> Input {
         T,
         sigma,
         wh2: clamped_wh,
+        wh_std,
         calc_hash: CalcHash::new(width_limit),
         width_limit,
     }
@@ -80,6 +80,7 @@ pub struct Input {
     pub T: usize,
     pub sigma: i64,
     pub wh2: Vec<(i64, i64)>,
+    pub wh_std: Vec<(f64, f64)>, // 箱の長さの推定値の標準偏差
     pub calc_hash: CalcHash,
     pub width_limit: i64,
 }
@@ -3,9 +3,13 @@
 
 mod beam;
 mod common;
+mod estimator;
 mod hash;
 mod input;
 mod measure;
+mod normal;
+mod packing;
+mod select;
 mod state;
 mod test;
 
@@ -13,7 +17,8 @@ use beam::{BeamSearch, Node};
 use common::get_time;
 use input::{read_input, Input};
 use rand_pcg::Pcg64Mcg;
-use state::State;
+use select::select;
+use state::{Op, State};
 
 fn solve(input: &Input) {
     let mut rng = Pcg64Mcg::new(0);
@@ -24,7 +29,15 @@ fn solve(input: &Input) {
     };
     let mut beam = BeamSearch::new(init_node);
     let width = if input.N <= 65 { 12000 } else { 10000 };
-    beam.solve(width, input.N, &input, &mut rng, true);
+    // 棚詰めの候補と、一般の配置の候補を合わせて選ぶ
+    let mut packings = beam.solve(width, input.N, &input, &mut rng, true);
+    packings.extend(packing::search(input));
+    for ops in select(&packings, input, &mut rng) {
+        println!("{}", ops.len());
+        for Op { p, r, d, b, .. } in ops.iter() {
+            println!("{} {} {} {}", p, if *r { 1 } else { 0 }, d, b);
+        }
+    }
 }
 
 fn main() {
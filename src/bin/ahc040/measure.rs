@@ -1,227 +1,302 @@
 use itertools::Itertools;
 use proconio::input_interactive;
-use rand::{seq::SliceRandom, Rng};
-use rand_pcg::Pcg64Mcg;
-use rustc_hash::FxHashMap;
 
-pub fn measure(N: usize, T: usize, sigma: i64, wh2: Vec<(i64, i64)>) -> Vec<(i64, i64)> {
-    let mut rng = Pcg64Mcg::new(10);
-    let mut t = 0;
-    let mut measures = vec![];
-    let mut measure_cnt = vec![vec![0; 2]; N];
-
-    let center_idx = {
-        let mut idx;
-        let mut d = 0;
-        'outer: loop {
-            for dir in [1, -1].iter() {
-                idx = N / 2 + (d * dir) as usize;
-                let (w, h) = wh2[idx];
-                if w < 7e4 as i64 && h < 7e4 as i64 {
-                    break 'outer;
-                }
-            }
-            d += 1;
+use crate::{
+    estimator::Estimator,
+    state::{Op, P0},
+};
+
+// 推定値の信頼区間の幅 (標準偏差の何倍か)
+const MARGIN: f64 = 3.0;
+// 測定に使うターン数の上限の割合
+const MAX_MEASURE_RATIO: f64 = 0.8;
+// 配置の W + H が推定誤差の標準偏差の何倍悪くなるか
+const ERROR_WEIGHT: f64 = 2.0;
+// 推定誤差によらない配置ごとの W + H のばらつき (箱の面積の和の平方根の何倍か)
+const PACKING_SPREAD_RATIO: f64 = 0.01;
+// 変数を追加しても減少量がこの割合以上増えなければ追加をやめる
+const MIN_STEP_RATIO: f64 = 0.01;
+
+/// 測定の問い合わせ先
+pub trait Judge {
+    /// 箱を置いて、(W, H) の測定値を返す
+    fn query(&mut self, cmds: &Vec<Op>) -> (i64, i64);
+}
+
+/// 標準入出力でジャッジとやりとりする
+pub struct Interactive;
+
+impl Judge for Interactive {
+    fn query(&mut self, cmds: &Vec<Op>) -> (i64, i64) {
+        println!("{}", cmds.len());
+        for op in cmds.iter() {
+            println!("{} {} {} {}", op.p, if op.r { 1 } else { 0 }, op.d, op.b);
+        }
+        input_interactive! {
+            w: i64, h: i64,
         }
-        idx
-    };
+        (w, h)
+    }
+}
 
-    while t < T {
-        let order = (0..N).collect_vec();
-        // 前半部分を横に置く
-        let order_width = order
-            .iter()
-            .take(center_idx)
-            .map(|x| {
-                let rotate = if t == 0 {
-                    false
-                } else {
-                    rng.gen_bool(measure_cnt[*x][0] as f64 / t as f64) // 横・縦の測定回数を均等にする
-                };
-                let w = if rotate { wh2[*x].1 } else { wh2[*x].0 };
-                (*x, rotate, w)
-            })
-            .collect_vec();
-
-        // 横に置いたものの中で最も幅が大きいものを探す
-        // これを基準にして縦に置く
-        let (max_width_idx, max_width_rotate, max_width) = order_width
-            .iter()
-            .take(order_width.len() - 1) // 最後の要素に縦置きすると、幅測定に影響が出るため除外
-            .max_by_key(|x| x.2)
-            .unwrap();
+/// 1回の測定の配置
+/// 横一列に row を並べ、row に含まれる base の上に column を積む
+/// W は row の幅の和、H は base の高さと column の高さの和になる
+struct Query {
+    row: Vec<(usize, bool)>,
+    base: usize,
+    column: Vec<(usize, bool)>,
+    measure_height: bool,
+    gain: f64,
+}
 
-        // 後半部分を縦に置く
-        let order_height = order
+impl Query {
+    fn row_vars(&self) -> Vec<usize> {
+        self.row
             .iter()
-            .skip(center_idx)
-            .map(|x| {
-                let rotate = if t == 0 {
-                    false
-                } else {
-                    rng.gen_bool(measure_cnt[*x][0] as f64 / t as f64) // 横・縦の測定回数を均等にする
-                };
-                let w = if rotate { wh2[*x].1 } else { wh2[*x].0 };
-                (*x, rotate, w)
-            })
-            .collect_vec();
-
-        let (_, _, first_width) = order_height[0]; // 縦置きの先頭
-
-        // 横置きの最大幅が、縦置きの先頭の幅より十分大きい場合に測定
-        if max_width - first_width > sigma {
-            t += 1;
-            println!("{}", N);
-            let mut now = -1;
-            let mut measure_width = FxHashMap::default();
-            for &(idx, rotate, _) in order_width.iter() {
-                println!("{} {} U {}", idx, if rotate { 1 } else { 0 }, now);
-                now = idx as i32;
-                measure_width.insert(idx, rotate);
-                measure_cnt[idx][rotate as usize] += 1;
-            }
+            .map(|&(idx, rotate)| Estimator::var_index(idx, rotate))
+            .collect()
+    }
+    fn column_vars(&self) -> Vec<usize> {
+        let base_rotate = self.row.iter().find(|x| x.0 == self.base).unwrap().1;
+        let mut vars = vec![Estimator::var_index(self.base, !base_rotate)];
+        for &(idx, rotate) in self.column.iter() {
+            vars.push(Estimator::var_index(idx, !rotate));
+        }
+        vars
+    }
+    fn cmds(&self) -> Vec<Op> {
+        // base の左隣の箱 (column はこの箱の右端に合わせて置く)
+        let base_prev = self
+            .row
+            .iter()
+            .take_while(|x| x.0 != self.base)
+            .last()
+            .map_or(-1, |x| x.0 as i32);
+        let op = |p: usize, r: bool, b: i32| Op {
+            p,
+            r,
+            d: 'U',
+            b,
+            pos: P0,
+            row: !0,
+        };
+        let mut cmds = vec![];
+        let mut prev = -1;
+        for &(idx, rotate) in self.row.iter() {
+            cmds.push(op(idx, rotate, prev));
+            prev = idx as i32;
+        }
+        for &(idx, rotate) in self.column.iter() {
+            cmds.push(op(idx, rotate, base_prev));
+        }
+        cmds.sort_by_key(|op| op.p);
+        cmds
+    }
+}
 
-            let mut measure_height = FxHashMap::default();
-            measure_height.insert(*max_width_idx, !*max_width_rotate);
-            for &(idx, rotate, _) in order_height.iter() {
-                println!(
-                    "{} {} U {}",
-                    idx,
-                    if rotate { 1 } else { 0 },
-                    *max_width_idx as i32 - 1 // 右端基準なので、置きたい箱の1つ前の箱の右端に合わせる
-                );
-                measure_height.insert(idx, !rotate);
-                measure_cnt[idx][rotate as usize] += 1;
+// 現在の変数の集合に、候補から分散の減少量が最も増える変数を貪欲に追加する
+// cands は (箱, 回転, 変数) で、同じ箱は1度しか使わない
+fn greedy(
+    estimator: &Estimator,
+    col_norm: &Vec<f64>,
+    vars: &mut Vec<usize>,
+    cands: &Vec<(usize, bool, usize)>,
+    used: &mut Vec<bool>,
+) -> (Vec<(usize, bool)>, f64) {
+    let m = 2 * estimator.n;
+    let cov = &estimator.cov;
+    // v = cov * a, q = a^T cov a
+    let mut v = vec![0.0; m];
+    for &j in vars.iter() {
+        for i in 0..m {
+            v[i] += cov[i][j];
+        }
+    }
+    let mut q = vars.iter().map(|&i| v[i]).sum::<f64>();
+    let mut norm = v.iter().map(|x| x * x).sum::<f64>();
+    let mut gain = norm / (q + estimator.noise_var());
+    let mut added = vec![];
+    loop {
+        let mut best = None;
+        let mut best_gain = gain * (1.0 + MIN_STEP_RATIO);
+        for (k, &(idx, _, j)) in cands.iter().enumerate() {
+            if used[idx] {
+                continue;
             }
-            input_interactive! {
-                w: i64, h: i64,
+            let dot = (0..m).map(|i| v[i] * cov[j][i]).sum::<f64>();
+            let next_norm = norm + 2.0 * dot + col_norm[j];
+            let next_q = q + 2.0 * v[j] + cov[j][j];
+            let next_gain = next_norm / (next_q + estimator.noise_var());
+            if next_gain > best_gain {
+                best_gain = next_gain;
+                best = Some((k, dot));
             }
-            // 測定方向と結果が分かれば、横置き、縦置きを区別する必要はない
-            measures.push((measure_width, w));
-            measures.push((measure_height, h));
         }
+        let Some((k, dot)) = best else {
+            break;
+        };
+        let (idx, rotate, j) = cands[k];
+        norm += 2.0 * dot + col_norm[j];
+        q += 2.0 * v[j] + cov[j][j];
+        for i in 0..m {
+            v[i] += cov[j][i];
+        }
+        gain = best_gain;
+        used[idx] = true;
+        vars.push(j);
+        added.push((idx, rotate));
     }
-
-    let mut state = State::new(wh2, sigma, measures);
-    state.solve();
-    state.wh
+    (added, gain)
 }
 
-struct State {
-    wh: Vec<(i64, i64)>,
-    sigma: i64,
-    // 箱の長さがwhだったとき、measuresに従って置いたときの長さ
-    lengths: Vec<i64>,
-    // 測定の際のindexと回転の情報(0: 横, 1: 縦)と測定値
-    measures: Vec<(FxHashMap<usize, bool>, i64)>,
-    // 各測定値の差の二乗和
-    score: i64,
+// 事後分散の減少量が大きくなる測定の配置を作る
+fn plan_query(estimator: &Estimator) -> Query {
+    let n = estimator.n;
+    let m = 2 * n;
+    let mean = &estimator.mean;
+    let cov = &estimator.cov;
+    let lower = |j: usize| mean[j] - MARGIN * cov[j][j].max(0.0).sqrt();
+    let upper = |j: usize| mean[j] + MARGIN * cov[j][j].max(0.0).sqrt();
+    let col_norm = (0..m)
+        .map(|j| cov[j].iter().map(|x| x * x).sum::<f64>())
+        .collect_vec();
+
+    // 前半の箱の中で、幅の下限が最も大きい箱を土台にする
+    let (base, base_rotate) = (0..(n / 2).max(1))
+        .cartesian_product([false, true])
+        .max_by(|a, b| {
+            let la = lower(Estimator::var_index(a.0, a.1));
+            let lb = lower(Estimator::var_index(b.0, b.1));
+            la.partial_cmp(&lb).unwrap()
+        })
+        .unwrap();
+    let base_width = lower(Estimator::var_index(base, base_rotate));
+
+    let mut used = vec![false; n];
+    used[base] = true;
+    let row_cands = (0..n)
+        .cartesian_product([false, true])
+        .map(|(idx, rotate)| (idx, rotate, Estimator::var_index(idx, rotate)))
+        .collect_vec();
+    let mut row_vars = vec![Estimator::var_index(base, base_rotate)];
+    let (mut row, row_gain) = greedy(estimator, &col_norm, &mut row_vars, &row_cands, &mut used);
+    row.push((base, base_rotate));
+    row.sort();
+
+    // 土台より確実に幅が狭く、土台より後に置く箱だけを積む
+    let column_cands = (base + 1..n)
+        .cartesian_product([false, true])
+        .filter(|&(idx, rotate)| upper(Estimator::var_index(idx, rotate)) < base_width)
+        .map(|(idx, rotate)| (idx, rotate, Estimator::var_index(idx, !rotate)))
+        .collect_vec();
+    let mut column_vars = vec![Estimator::var_index(base, !base_rotate)];
+    let (column, column_gain) = greedy(
+        estimator,
+        &col_norm,
+        &mut column_vars,
+        &column_cands,
+        &mut used,
+    );
+
+    // 積んだ高さが row の他の箱の高さを確実に上回るときのみ H を使う
+    let stack_mean = column_vars.iter().map(|&j| mean[j]).sum::<f64>();
+    let stack_var = column_vars
+        .iter()
+        .map(|&i| column_vars.iter().map(|&j| cov[i][j]).sum::<f64>())
+        .sum::<f64>();
+    let row_height = row
+        .iter()
+        .filter(|x| x.0 != base)
+        .map(|&(idx, rotate)| upper(Estimator::var_index(idx, !rotate)))
+        .fold(0.0, f64::max);
+    let measure_height =
+        !column.is_empty() && stack_mean - MARGIN * stack_var.max(0.0).sqrt() > row_height;
+
+    Query {
+        row,
+        base,
+        column: if measure_height { column } else { vec![] },
+        measure_height,
+        gain: row_gain + if measure_height { column_gain } else { 0.0 },
+    }
 }
 
-impl State {
-    fn new(wh2: Vec<(i64, i64)>, sigma: i64, measures: Vec<(FxHashMap<usize, bool>, i64)>) -> Self {
-        let mut score = 0;
-        let mut lengths = vec![];
-        for (measure, measure_len) in measures.iter() {
-            let mut len = 0;
-            for (idx, rotate) in measure.iter() {
-                let (w, h) = wh2[*idx];
-                if *rotate {
-                    len += h;
-                } else {
-                    len += w;
-                }
-            }
-            let diff = (measure_len - len).abs();
-            score += diff * diff;
-            lengths.push(len);
+/// 測定を行い、箱の長さの事後分布の (平均, 標準偏差) と測定に使ったターン数を返す
+/// 配置の W + H は推定誤差の分だけ悪くなり、配置のターンが増えるほど W + H の最小値は下がる
+/// 1回の測定で減る W + H の推定誤差が、1回の配置で下がる W + H の最小値の期待値を下回ったら、残りのターンは配置に回す
+pub fn measure<J: Judge>(
+    judge: &mut J,
+    N: usize,
+    T: usize,
+    sigma: i64,
+    wh2: Vec<(i64, i64)>,
+) -> (Vec<(i64, i64)>, Vec<(f64, f64)>, usize) {
+    let mut estimator = Estimator::new(&wh2, sigma);
+    let max_measure_num = ((T as f64 * MAX_MEASURE_RATIO) as usize).min(T - 1);
+    let area = wh2.iter().map(|&(w, h)| w as f64 * h as f64).sum::<f64>();
+    let spread = PACKING_SPREAD_RATIO * area.sqrt();
+    let expected = expected_max(T);
+    // W と H はそれぞれ約 √N 個の箱の長さの和なので、W + H の分散は事後分散の和の 1/√N 倍とみなす
+    let error = |trace: f64| (trace.max(0.0) / (N as f64).sqrt()).sqrt();
+    let mut t = 0;
+    while t < max_measure_num && N >= 2 {
+        let query = plan_query(&estimator);
+        let trace = estimator.trace();
+        let next_error = error(trace - query.gain);
+        // 配置ごとの W + H は推定誤差と配置自体のばらつきの分だけばらつくとみなす
+        // 測定すると配置のターンが T - t から T - t - 1 に減る
+        let packing_gain =
+            (next_error.powi(2) + spread.powi(2)).sqrt() * (expected[T - t] - expected[T - t - 1]);
+        if ERROR_WEIGHT * (error(trace) - next_error) < packing_gain {
+            break;
         }
-        eprintln!("Initial measure score : {}", score);
-
-        Self {
-            wh: wh2,
-            sigma,
-            lengths,
-            measures,
-            score,
+        t += 1;
+        let (w, h) = judge.query(&query.cmds());
+        estimator.observe(query.row_vars(), w);
+        if query.measure_height {
+            estimator.observe(query.column_vars(), h);
         }
     }
-    fn solve(&mut self) {
-        let n = self.wh.len();
-        let max_delta = self.sigma / 20;
-
-        for sep in 1..=200 {
-            let delta = (max_delta / sep).max(1);
-
-            for idx in 0..n {
-                for r in 0..=1 {
-                    let rotate = if r == 0 { false } else { true };
-
-                    let dir = {
-                        let mut next_score = self.score;
-                        for (length, measure) in self.lengths.iter().zip(self.measures.iter()) {
-                            if measure.0.contains_key(&idx) && measure.0[&idx] == rotate {
-                                let before_diff = (length - measure.1).abs();
-                                let after_diff = (length + delta - measure.1).abs();
-                                next_score += after_diff * after_diff - before_diff * before_diff;
-                            }
-                        }
-                        if next_score < self.score {
-                            1
-                        } else {
-                            -1
-                        }
-                    };
-
-                    let mut l = 1;
-                    let mut r = 100;
-                    while r - l > 1 {
-                        let m = (l + r) / 2;
-                        let d = delta * m * dir;
-                        let mut next_score = self.score;
-
-                        for (length, measure) in self.lengths.iter().zip(self.measures.iter()) {
-                            if measure.0.contains_key(&idx) && measure.0[&idx] == rotate {
-                                let before_diff = (length - measure.1).abs();
-                                let after_diff = (length + d - measure.1).abs();
-                                next_score += after_diff * after_diff - before_diff * before_diff;
-                            }
-                        }
-
-                        let diff_score = next_score - self.score;
-
-                        if diff_score < 0 {
-                            l = m;
-                        } else {
-                            r = m;
-                        }
-                    }
-
-                    let mut modified = l;
-                    modified /= 20;
-                    if modified == 0 {
-                        continue;
-                    }
-                    let modified_delta = delta * modified * dir;
-                    let mut next_score = self.score;
-
-                    for (length, measure) in self.lengths.iter_mut().zip(self.measures.iter()) {
-                        if measure.0.contains_key(&idx) && measure.0[&idx] == rotate {
-                            let before_diff = (*length - measure.1).abs();
-                            let after_diff = (*length + modified_delta - measure.1).abs();
-                            *length += modified_delta;
-                            next_score += after_diff * after_diff - before_diff * before_diff;
-                        }
-                    }
-                    if rotate {
-                        self.wh[idx].1 += modified_delta;
-                    } else {
-                        self.wh[idx].0 += modified_delta;
-                    }
-                    self.score = next_score;
-                }
-            }
+    eprintln!("measure_num = {}", t);
+
+    estimator.solve();
+    eprintln!("Modified score: {:.0}", estimator.residual());
+    (estimator.wh(), estimator.std(), t)
+}
+
+// 標準正規分布に従う k 個の値の最大値の期待値を k = 0..=n について求める
+// E[max] = ∫ x d(Φ(x)^k) を区分求積で計算する
+fn expected_max(n: usize) -> Vec<f64> {
+    const STEP: f64 = 0.01;
+    let xs = (-800..=800).map(|i| i as f64 * STEP).collect_vec();
+    let mut cdf = vec![0.0; xs.len()];
+    let mut acc = 0.0;
+    for (i, &x) in xs.iter().enumerate() {
+        acc += (-x * x / 2.0).exp() / (2.0 * std::f64::consts::PI).sqrt() * STEP;
+        cdf[i] = acc.min(1.0);
+    }
+    let mut ret = vec![0.0; n + 1];
+    for k in 1..=n {
+        let mut prev = 0.0;
+        for (i, &x) in xs.iter().enumerate() {
+            let cur = cdf[i].powi(k as i32);
+            ret[k] += x * (cur - prev);
+            prev = cur;
         }
-        eprintln!("Modified score: {}", self.score);
+    }
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::expected_max;
+
+    #[test]
+    fn test_expected_max() {
+        let e = expected_max(10);
+        assert!(e[1].abs() < 1e-2);
+        assert!((e[2] - 0.5642).abs() < 1e-2);
+        assert!((e[10] - 1.5388).abs() < 1e-2);
     }
 }
@@ -0,0 +1,175 @@
+use rustc_hash::FxHashSet;
+
+use crate::{
+    common::SetMinMax,
+    input::Input,
+    state::{Op, P0},
+};
+
+// 一般の配置の探索のビーム幅
+const WIDTH: usize = 20;
+
+/// 置いた箱の座標 (x1, x2, y1, y2)
+pub type Rect = (i64, i64, i64, i64);
+
+/// tools/ahc040 の State::query と同じ規則で、(p, r, d, b) の操作を1つずつ適用する状態
+#[derive(Debug, Clone)]
+pub struct Packing {
+    pub pos: Vec<Option<Rect>>,
+    pub placed: Vec<usize>,
+    pub W: i64,
+    pub H: i64,
+    pub area: i64,
+    pub ops: Vec<Op>,
+}
+
+impl Packing {
+    pub fn new(n: usize) -> Self {
+        Self {
+            pos: vec![None; n],
+            placed: vec![],
+            W: 0,
+            H: 0,
+            area: 0,
+            ops: vec![],
+        }
+    }
+    pub fn calc(&self, p: usize, r: bool, d: char, b: i32, wh: &Vec<(i64, i64)>) -> Rect {
+        let (mut w, mut h) = wh[p];
+        if r {
+            std::mem::swap(&mut w, &mut h);
+        }
+        let base = if b < 0 { None } else { self.pos[b as usize] };
+        if d == 'U' {
+            let x1 = base.map_or(0, |q| q.1);
+            let x2 = x1 + w;
+            let y1 = self
+                .placed
+                .iter()
+                .filter_map(|&i| self.pos[i])
+                .filter(|q| x1.max(q.0) < x2.min(q.1))
+                .map(|q| q.3)
+                .max()
+                .unwrap_or(0);
+            (x1, x2, y1, y1 + h)
+        } else {
+            let y1 = base.map_or(0, |q| q.3);
+            let y2 = y1 + h;
+            let x1 = self
+                .placed
+                .iter()
+                .filter_map(|&i| self.pos[i])
+                .filter(|q| y1.max(q.2) < y2.min(q.3))
+                .map(|q| q.1)
+                .max()
+                .unwrap_or(0);
+            (x1, x1 + w, y1, y2)
+        }
+    }
+    pub fn place(&mut self, op: Op, wh: &Vec<(i64, i64)>) -> Rect {
+        let rect = self.calc(op.p, op.r, op.d, op.b, wh);
+        self.pos[op.p] = Some(rect);
+        self.placed.push(op.p);
+        self.W.setmax(rect.1);
+        self.H.setmax(rect.3);
+        self.area += wh[op.p].0 * wh[op.p].1;
+        self.ops.push(op);
+        rect
+    }
+    /// 置かなかった箱の縦横の長さの和を含めたスコア
+    pub fn score(&self, wh: &Vec<(i64, i64)>) -> i64 {
+        let rest = (0..wh.len())
+            .filter(|&i| self.pos[i].is_none())
+            .map(|i| wh[i].0 + wh[i].1)
+            .sum::<i64>();
+        self.W + self.H + rest
+    }
+}
+
+/// 箱の長さが wh のときに、ジャッジと同じ規則で ops を置いたときのスコア
+pub fn simulate(ops: &[Op], wh: &Vec<(i64, i64)>) -> i64 {
+    let mut packing = Packing::new(wh.len());
+    for &op in ops.iter() {
+        packing.place(op, wh);
+    }
+    packing.score(wh)
+}
+
+// 置いた後の W, H と残りの箱の面積から、最終的な W + H を予測する
+// 外接長方形の空きに入りきらない面積の分だけ、短い辺が伸びるとみなす
+fn predict(W: i64, H: i64, area: i64, rest_area: i64) -> i64 {
+    let free = W * H - area;
+    let over = (rest_area - free).max(0);
+    W + H + over / W.max(H).max(1)
+}
+
+/// 任意の (r, d, b) を候補にして、箱を番号順に全て置くビームサーチ
+/// U では x1、L では y1 が同じになる b は1つにまとめる
+pub fn search(input: &Input) -> Vec<Vec<Op>> {
+    let wh = &input.wh2;
+    let total_area = wh.iter().map(|&(w, h)| w * h).sum::<i64>();
+    let mut nodes = vec![Packing::new(input.N)];
+    for p in 0..input.N {
+        let rest_area = total_area - nodes[0].area - wh[p].0 * wh[p].1;
+        // (予測スコア, 親, 操作)
+        let mut cands = vec![];
+        for (k, node) in nodes.iter().enumerate() {
+            for d in ['U', 'L'] {
+                let mut starts = FxHashSet::default();
+                let bases = std::iter::once(-1).chain(node.placed.iter().map(|&i| i as i32));
+                for b in bases {
+                    let start = if b < 0 {
+                        0
+                    } else {
+                        let q = node.pos[b as usize].unwrap();
+                        if d == 'U' {
+                            q.1
+                        } else {
+                            q.3
+                        }
+                    };
+                    if !starts.insert(start) {
+                        continue;
+                    }
+                    for r in [false, true] {
+                        let rect = node.calc(p, r, d, b, wh);
+                        // 極端に細長い配置は除く
+                        if b >= 0 && rect.1.max(rect.3) > input.width_limit {
+                            continue;
+                        }
+                        let area = node.area + wh[p].0 * wh[p].1;
+                        let eval = predict(node.W.max(rect.1), node.H.max(rect.3), area, rest_area);
+                        let op = Op {
+                            p,
+                            r,
+                            d,
+                            b,
+                            pos: P0,
+                            row: !0,
+                        };
+                        cands.push((eval, k, op));
+                    }
+                }
+            }
+        }
+        cands.sort_unstable_by_key(|c| c.0);
+        let mut set = FxHashSet::default();
+        let mut next_nodes = vec![];
+        for &(_, k, op) in cands.iter() {
+            let mut node = nodes[k].clone();
+            let rect = node.place(op, wh);
+            // 外接長方形と置いた位置が同じ状態は重複とみなす
+            if !set.insert((node.W, node.H, rect)) {
+                continue;
+            }
+            next_nodes.push(node);
+            if next_nodes.len() == WIDTH {
+                break;
+            }
+        }
+        nodes = next_nodes;
+    }
+    nodes.sort_by_key(|node| node.score(wh));
+    eprintln!("General = {}", nodes[0].score(wh));
+    nodes.into_iter().map(|node| node.ops).collect()
+}
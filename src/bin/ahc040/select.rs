@@ -0,0 +1,77 @@
+use rand_pcg::Pcg64Mcg;
+
+use crate::{common::SetMinMax, input::Input, normal::Normal, packing::simulate, state::Op};
+
+// 棚詰めのビームサーチから取る候補の数の上限 (提出ターン数の何倍か)
+const POOL_RATIO: usize = 3;
+const POOL_MAX: usize = 300;
+// 箱の長さのサンプル数
+const SAMPLE_NUM: usize = 30;
+
+/// 選択の候補にする配置の数
+pub fn pool_size(T: usize) -> usize {
+    (T * POOL_RATIO).min(POOL_MAX)
+}
+
+/// 候補の配置から、提出する T 個の配置を選ぶ
+/// 推定した分布から箱の長さをサンプルし、各サンプルで選んだ配置の W + H の最小値の平均が小さくなるように貪欲に選ぶ
+pub fn select(packings: &Vec<Vec<Op>>, input: &Input, rng: &mut Pcg64Mcg) -> Vec<Vec<Op>> {
+    let pool = packings.len();
+    let mut normal = Normal::new(0.0, 1.0);
+    let samples = (0..SAMPLE_NUM)
+        .map(|_| {
+            (0..input.N)
+                .map(|i| {
+                    let (w, h) = input.wh2[i];
+                    let (sw, sh) = input.wh_std[i];
+                    (
+                        (w as f64 + sw * normal.sample(rng)).round().max(1.0) as i64,
+                        (h as f64 + sh * normal.sample(rng)).round().max(1.0) as i64,
+                    )
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    let scores = packings
+        .iter()
+        .map(|ops| {
+            samples
+                .iter()
+                .map(|wh| simulate(ops, wh))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    let mut selected = vec![];
+    let mut best = vec![i64::MAX; SAMPLE_NUM];
+    while selected.len() < input.T.min(pool) {
+        let k = (0..pool)
+            .filter(|k| !selected.contains(k))
+            .min_by_key(|&k| {
+                let expected = (0..SAMPLE_NUM)
+                    .map(|s| best[s].min(scores[k][s]))
+                    .sum::<i64>();
+                // 期待値が変わらない場合は、単独で良い配置を優先する
+                (expected, scores[k].iter().sum::<i64>())
+            })
+            .unwrap();
+        for s in 0..SAMPLE_NUM {
+            best[s].setmin(scores[k][s]);
+        }
+        selected.push(k);
+    }
+    eprintln!(
+        "Expected best = {:.0}",
+        best.iter().sum::<i64>() as f64 / SAMPLE_NUM as f64
+    );
+
+    // 候補が足りない場合は最も良い配置を繰り返す
+    let mut ret = selected
+        .iter()
+        .map(|&k| packings[k].clone())
+        .collect::<Vec<_>>();
+    while ret.len() < input.T {
+        ret.push(packings[selected[0]].clone());
+    }
+    ret
+}
@@ -250,4 +250,196 @@ mod tests {
         println!("{}", total);
         writeln!(file, "{}", total).unwrap();
     }
+
+    // 推定器の評価
+    // tools/ahc040/in の入力に対して測定フェーズのみをシミュレータで実行し、推定誤差を集計する
+    // exp: makers stest ahc040 estimator
+
+    use crate::input::{MAX, MIN};
+    use crate::measure::{measure, Judge};
+    use crate::packing::Packing;
+    use crate::state::Op;
+
+    struct Simulator {
+        wh: Vec<(i64, i64)>,
+        es: Vec<(i64, i64)>,
+        turn: usize,
+    }
+
+    impl Judge for Simulator {
+        fn query(&mut self, cmds: &Vec<Op>) -> (i64, i64) {
+            let mut packing = Packing::new(self.wh.len());
+            for &op in cmds.iter() {
+                packing.place(op, &self.wh);
+            }
+            let (ew, eh) = self.es[self.turn];
+            self.turn += 1;
+            (
+                (packing.W + ew).max(1).min(1e9 as i64),
+                (packing.H + eh).max(1).min(1e9 as i64),
+            )
+        }
+    }
+
+    #[derive(Debug)]
+    struct EstimatorResult {
+        test_number: usize,
+        N: usize,
+        T: usize,
+        sigma: i64,
+        measure_num: usize,
+        before: f64, // 測定前の二乗平均平方根誤差
+        after: f64,  // 測定後の二乗平均平方根誤差
+    }
+
+    fn rmse(est: &Vec<(i64, i64)>, wh: &Vec<(i64, i64)>) -> f64 {
+        let sum = est
+            .iter()
+            .zip(wh.iter())
+            .map(|(&(w0, h0), &(w1, h1))| {
+                let (w0, h0) = (w0.max(MIN).min(MAX), h0.max(MIN).min(MAX));
+                ((w0 - w1) * (w0 - w1) + (h0 - h1) * (h0 - h1)) as f64
+            })
+            .sum::<f64>();
+        (sum / (2 * wh.len()) as f64).sqrt()
+    }
+
+    fn run_estimator(test_number: usize, _: usize) -> EstimatorResult {
+        let mut contents = String::new();
+        File::open(format!("tools/ahc040/in/{:04}.txt", test_number))
+            .unwrap()
+            .read_to_string(&mut contents)
+            .unwrap();
+        let source = proconio::source::once::OnceSource::from(contents.as_str());
+        proconio::input! {
+            from source,
+            N: usize, T: usize, sigma: i64,
+            wh2: [(i64, i64); N],
+            wh: [(i64, i64); N],
+            es: [(i64, i64); T],
+        }
+        let wh2 = wh2
+            .iter()
+            .map(|&(w, h)| (w.max(MIN).min(MAX), h.max(MIN).min(MAX)))
+            .collect::<Vec<_>>();
+        let mut simulator = Simulator {
+            wh: wh.clone(),
+            es,
+            turn: 0,
+        };
+        let (est, _, measure_num) = measure(&mut simulator, N, T, sigma, wh2.clone());
+        EstimatorResult {
+            test_number,
+            N,
+            T,
+            sigma,
+            measure_num,
+            before: rmse(&wh2, &wh),
+            after: rmse(&est, &wh),
+        }
+    }
+
+    #[test]
+    fn estimator() {
+        let job_num = 4;
+        let test_case_num = 100;
+        let results = cocurrent(job_num, run_estimator, vec![0; test_case_num]);
+
+        let mut file = File::create("estimator.csv").unwrap();
+        writeln!(file, "test_num,N,T,sigma,measure_num,before,after").unwrap();
+        for r in results.iter() {
+            writeln!(
+                file,
+                "{:04},{},{},{},{},{:.1},{:.1}",
+                r.test_number, r.N, r.T, r.sigma, r.measure_num, r.before, r.after
+            )
+            .unwrap();
+        }
+
+        // sigma と N の区間ごとに、誤差の二乗平均平方根を集計
+        let report = |name: &str, key: &dyn Fn(&EstimatorResult) -> usize, labels: &[&str]| {
+            println!("{}", name);
+            for (k, label) in labels.iter().enumerate() {
+                let group = results.iter().filter(|r| key(r) == k).collect::<Vec<_>>();
+                if group.is_empty() {
+                    continue;
+                }
+                let mean = |f: &dyn Fn(&EstimatorResult) -> f64| {
+                    (group.iter().map(|r| f(r) * f(r)).sum::<f64>() / group.len() as f64).sqrt()
+                };
+                println!(
+                    "  {}: cases={}, before={:.1}, after={:.1}, measure_num={:.1}",
+                    label,
+                    group.len(),
+                    mean(&|r| r.before),
+                    mean(&|r| r.after),
+                    group.iter().map(|r| r.measure_num).sum::<usize>() as f64 / group.len() as f64,
+                );
+            }
+        };
+        report(
+            "sigma",
+            &|r| ((r.sigma - 1000) / 3000).min(2) as usize,
+            &["1000-3999", "4000-6999", "7000-10000"],
+        );
+        report(
+            "N",
+            &|r| ((r.N - 30) / 24).min(2),
+            &["30-53", "54-77", "78-100"],
+        );
+        report("all", &|_| 0, &["all"]);
+    }
+
+    // 測定回数が sigma と N に応じて変わることを確かめる
+    // 入力は tools/ahc040 の生成方法に合わせて乱数で作る
+    // exp: makers stest ahc040 measure_num
+
+    use crate::normal::Normal;
+    use rand::Rng;
+    use rand_pcg::Pcg64Mcg;
+
+    fn run_measure_num(N: usize, T: usize, sigma: i64, seed: u64) -> usize {
+        let mut rng = Pcg64Mcg::new(seed as u128);
+        let mut normal = Normal::new(0.0, sigma as f64);
+        let L = rng.gen_range(10000..=50000);
+        let wh = (0..N)
+            .map(|_| (rng.gen_range(L..=100000), rng.gen_range(L..=100000)))
+            .collect::<Vec<(i64, i64)>>();
+        let mut noisy = |x: i64| (x as f64 + normal.sample(&mut rng)).round() as i64;
+        let wh2 = wh
+            .iter()
+            .map(|&(w, h)| (noisy(w).max(MIN).min(MAX), noisy(h).max(MIN).min(MAX)))
+            .collect::<Vec<_>>();
+        let es = (0..T).map(|_| (noisy(0), noisy(0))).collect::<Vec<_>>();
+        let mut simulator = Simulator { wh, es, turn: 0 };
+        measure(&mut simulator, N, T, sigma, wh2).2
+    }
+
+    #[test]
+    fn measure_num() {
+        let T = 200;
+        let seed_num = 3;
+        let mut table = vec![];
+        for N in [30, 100] {
+            for sigma in [1000, 10000] {
+                let sum = (0..seed_num)
+                    .map(|seed| run_measure_num(N, T, sigma, seed))
+                    .sum::<usize>();
+                println!(
+                    "N={}, T={}, sigma={}: measure_num={}",
+                    N,
+                    T,
+                    sigma,
+                    sum / seed_num as usize
+                );
+                table.push(sum);
+            }
+        }
+        // sigma が大きいほど推定誤差の影響が大きいので多く測定する
+        assert!(table[0] < table[1]);
+        assert!(table[2] < table[3]);
+        // N によっても測定回数は変わる
+        assert!(table[0] != table[2]);
+        assert!(table[1] != table[3]);
+    }
 }
@@ -0,0 +1,142 @@
+use itertools::Itertools;
+use rand::Rng;
+use rand_pcg::Pcg64Mcg;
+
+use crate::{common::get_time, input::Input, state::State};
+
+// 各構築法の後に焼きなます時間
+const TRIAL_TIME: f64 = 0.1;
+// 乱択貪欲のやり直しに使う時間
+const RESTART_TIME: f64 = 0.1;
+// 乱択貪欲で根の順番に加えるノイズの大きさ
+const NOISE: i64 = 10;
+
+type Construction = fn(&Input, &mut Pcg64Mcg) -> State;
+
+/// 初期解の構築法の一覧 (名前, 構築法)
+/// 構築法を増やすときはここに追加する
+pub const CONSTRUCTIONS: [(&str, Construction); 3] = [
+    ("greedy", greedy),
+    ("bfs_layer", bfs_layer),
+    ("random_greedy", random_greedy),
+];
+
+/// 全ての構築法で初期解を作って短く焼きなまし、最もスコアの高い解を返す
+pub fn construct(input: &Input, rng: &mut Pcg64Mcg) -> State {
+    let mut best: Option<State> = None;
+    for (name, construction) in CONSTRUCTIONS.iter() {
+        let mut state = construction(input, rng);
+        let initial = state.score;
+        state.annealing_until(input, get_time() + TRIAL_TIME);
+        eprintln!("{}: {} -> {}", name, initial, state.score);
+        if best.as_ref().map_or(true, |b| state.score > b.score) {
+            best = Some(state);
+        }
+    }
+    best.unwrap()
+}
+
+// 美しさの昇順に根を選ぶ貪欲
+fn greedy(input: &Input, _: &mut Pcg64Mcg) -> State {
+    let mut state = State::new(input);
+    state.greedy(input);
+    state
+}
+
+// 美しさの低い頂点から幅優先探索で層を作る
+fn bfs_layer(input: &Input, _: &mut Pcg64Mcg) -> State {
+    let mut state = State::new(input);
+    state.bfs_layer(input);
+    state
+}
+
+// 根の順番にノイズを加えた貪欲を時間いっぱいやり直す
+fn random_greedy(input: &Input, rng: &mut Pcg64Mcg) -> State {
+    let tle = get_time() + RESTART_TIME;
+    let mut best: Option<State> = None;
+    while best.is_none() || get_time() < tle {
+        let order = (0..input.N)
+            .map(|i| (input.A[i] + rng.gen_range(0..=NOISE), i))
+            .sorted()
+            .map(|(_, i)| i)
+            .collect_vec();
+        let mut state = State::new(input);
+        state.greedy_with_order(input, &order);
+        if best.as_ref().map_or(true, |b| state.score > b.score) {
+            best = Some(state);
+        }
+    }
+    best.unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use rand_pcg::Pcg64Mcg;
+
+    use super::{construct, CONSTRUCTIONS};
+    use crate::{
+        common::get_time,
+        input::{make_input, Input},
+        state::State,
+    };
+
+    // 5x6 の格子グラフ
+    fn grid_input() -> Input {
+        let (h, w) = (5, 6);
+        let N = h * w;
+        let A = (0..N).map(|i| (i * 37 % 100) as i64).collect::<Vec<_>>();
+        let mut UV = vec![];
+        for i in 0..h {
+            for j in 0..w {
+                if i + 1 < h {
+                    UV.push((i * w + j, (i + 1) * w + j));
+                }
+                if j + 1 < w {
+                    UV.push((i * w + j, i * w + j + 1));
+                }
+            }
+        }
+        make_input(N, UV.len(), 10, A, UV)
+    }
+
+    // 高さ制約を満たす森になっていて、スコアが一致するか確認する
+    fn check(input: &Input, state: &State, name: &str) {
+        let mut score = 1;
+        for v in 0..input.N {
+            let node = &state.forest.nodes[v];
+            if node.parent >= 0 {
+                let p = node.parent as usize;
+                assert!(input.G[v].contains(&p), "{}", name);
+                assert_eq!(node.h, state.forest.nodes[p].h + 1, "{}", name);
+            } else {
+                assert_eq!(node.h, 0, "{}", name);
+            }
+            assert!(node.h <= input.H as i64, "{}", name);
+            score += input.A[v] * (node.h + 1);
+        }
+        assert_eq!(state.score, score, "{}", name);
+    }
+
+    #[test]
+    fn test_constructions() {
+        let input = grid_input();
+        let mut rng = Pcg64Mcg::new(0);
+        for (name, construction) in CONSTRUCTIONS.iter() {
+            let state = construction(&input, &mut rng);
+            check(&input, &state, name);
+        }
+    }
+
+    #[test]
+    fn test_short_annealing() {
+        // 時刻の確認より短い時間で焼きなましても、温度が負にならずに終わる
+        let input = grid_input();
+        let mut rng = Pcg64Mcg::new(0);
+        let mut state = construct(&input, &mut rng);
+        check(&input, &state, "construct");
+        for window in [0.0, 1e-6, 1e-4, 1e-3] {
+            state.annealing_until(&input, get_time() + window);
+            check(&input, &state, "annealing");
+        }
+    }
+}
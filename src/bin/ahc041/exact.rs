@@ -0,0 +1,151 @@
+use crate::input::Input;
+
+// 各頂点の深さ d を決める問題として解く
+// d > 0 の頂点は深さ d - 1 の隣接頂点を親にできるので、
+// 全ての d > 0 の頂点に深さ d - 1 の隣接頂点があれば森になる
+
+#[derive(Debug, Clone)]
+pub struct ExactResult {
+    pub score: i64,
+    pub parents: Vec<i64>,
+    pub proven: bool, // 探索を打ち切らずに最適性を示せたか
+    pub nodes: usize, // 探索したノード数
+}
+
+/// 分枝限定法で最適解を求める
+/// lower より良い解が見つからなければ score は lower のまま、parents は空になる
+/// 探索ノード数が node_limit を超えたら打ち切る
+pub fn solve_exact(input: &Input, lower: i64, node_limit: usize) -> ExactResult {
+    let N = input.N;
+    let H = input.H.min(N - 1);
+    // 美しさの降順に深さを決める
+    let mut order = (0..N).collect::<Vec<_>>();
+    order.sort_by_key(|&v| -input.A[v]);
+    // 上界: 残りの頂点を全て深さ H にしたときのスコア
+    let mut rest = vec![0; N + 1];
+    for k in (0..N).rev() {
+        rest[k] = rest[k + 1] + input.A[order[k]] * (H as i64 + 1);
+    }
+    let mut search = Search {
+        input,
+        H,
+        order,
+        rest,
+        depth: vec![None; N],
+        best_score: lower,
+        best_depth: vec![],
+        nodes: 0,
+        node_limit,
+    };
+    let proven = search.dfs(0, 1);
+    let parents = if search.best_depth.is_empty() {
+        vec![]
+    } else {
+        to_parents(input, &search.best_depth)
+    };
+    ExactResult {
+        score: search.best_score,
+        parents,
+        proven,
+        nodes: search.nodes,
+    }
+}
+
+struct Search<'a> {
+    input: &'a Input,
+    H: usize,
+    order: Vec<usize>,
+    rest: Vec<i64>,
+    depth: Vec<Option<usize>>,
+    best_score: i64,
+    best_depth: Vec<usize>,
+    nodes: usize,
+    node_limit: usize,
+}
+
+impl<'a> Search<'a> {
+    // 打ち切らずに探索を終えたら true
+    fn dfs(&mut self, k: usize, score: i64) -> bool {
+        self.nodes += 1;
+        if self.nodes > self.node_limit {
+            return false;
+        }
+        if k == self.order.len() {
+            if score > self.best_score {
+                self.best_score = score;
+                self.best_depth = self.depth.iter().map(|d| d.unwrap()).collect();
+            }
+            return true;
+        }
+        if score + self.rest[k] <= self.best_score {
+            return true;
+        }
+        let v = self.order[k];
+        // 隣接頂点の深さの最大値 + 1 より深くはできない
+        let cap = self.input.G[v]
+            .iter()
+            .map(|&w| self.depth[w].map_or(self.H, |d| d + 1))
+            .max()
+            .unwrap_or(0)
+            .min(self.H);
+        for d in (0..=cap).rev() {
+            self.depth[v] = Some(d);
+            if self.is_consistent(v) {
+                let gain = self.input.A[v] * (d as i64 + 1);
+                if !self.dfs(k + 1, score + gain) {
+                    self.depth[v] = None;
+                    return false;
+                }
+            }
+        }
+        self.depth[v] = None;
+        true
+    }
+    // v と、深さを決めた v の隣接頂点が、親になりうる頂点を持つか
+    fn is_consistent(&self, v: usize) -> bool {
+        self.has_parent(v)
+            && self.input.G[v]
+                .iter()
+                .all(|&w| self.depth[w].is_none() || self.has_parent(w))
+    }
+    fn has_parent(&self, v: usize) -> bool {
+        let d = self.depth[v].unwrap();
+        d == 0
+            || self.input.G[v]
+                .iter()
+                .any(|&w| self.depth[w].map_or(true, |dw| dw + 1 == d))
+    }
+}
+
+fn to_parents(input: &Input, depth: &[usize]) -> Vec<i64> {
+    (0..input.N)
+        .map(|v| {
+            if depth[v] == 0 {
+                -1
+            } else {
+                input.G[v]
+                    .iter()
+                    .find(|&&w| depth[w] + 1 == depth[v])
+                    .map(|&w| w as i64)
+                    .unwrap()
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::solve_exact;
+    use crate::input::make_input;
+
+    #[test]
+    fn test_solve_exact() {
+        // 0 - 1 - 2 - 3 のパスで H = 2
+        let input = make_input(4, 3, 2, vec![1, 2, 3, 4], vec![(0, 1), (1, 2), (2, 3)]);
+        let result = solve_exact(&input, 0, 1000000);
+        assert!(result.proven);
+        // 1 を根にして 0, 2 を深さ 1、3 を深さ 2 にする: 1 + 1*2 + 2*1 + 3*2 + 4*3 = 23
+        assert_eq!(result.score, 23);
+        assert_eq!(result.parents, vec![1, -1, 1, 2]);
+    }
+}
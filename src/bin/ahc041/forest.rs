@@ -0,0 +1,184 @@
+#[derive(Debug, Clone)]
+pub struct Node {
+    pub num: usize,
+    pub h: i64,
+    pub parent: i64,
+    pub children: Vec<usize>,
+    pub sum_A: i64,
+    pub hmax: i64,
+}
+
+impl Node {
+    pub fn is_root(&self) -> bool {
+        self.parent == -1
+    }
+    pub fn is_leaf(&self) -> bool {
+        self.children.is_empty()
+    }
+}
+
+/// 根付き森で、各ノードの深さ h、部分木の重みの和 sum_A、部分木の最大の深さ hmax を保つ
+/// 深さの上限が小さい (H = 10) ので、祖先は親をたどり、深さは部分木をたどって更新する
+/// - 祖先の hmax は変わらなくなった所で更新をやめる
+/// - 付け替えは部分木を1回だけたどる
+/// - 閉路の判定は部分木ではなく付け替え先の祖先をたどる
+#[derive(Debug, Clone)]
+pub struct Forest {
+    pub nodes: Vec<Node>,
+}
+
+impl Forest {
+    /// 全てのノードが根の森
+    pub fn new(A: &[i64]) -> Self {
+        Self {
+            nodes: A
+                .iter()
+                .enumerate()
+                .map(|(i, &a)| Node {
+                    num: i,
+                    h: 0,
+                    parent: -1,
+                    children: vec![],
+                    sum_A: a,
+                    hmax: 0,
+                })
+                .collect(),
+        }
+    }
+    // ノードを親から切り離して根にし、スコアの差分を返す
+    pub fn cut(&mut self, node_id: usize) -> i64 {
+        if self.nodes[node_id].is_root() {
+            return 0;
+        }
+        self.detach(node_id);
+        let diff_h = -self.nodes[node_id].h;
+        self.shift(node_id, diff_h);
+        diff_h * self.nodes[node_id].sum_A
+    }
+    // 根のノードを parent_id の子にし、スコアの差分を返す
+    pub fn link(&mut self, node_id: usize, parent_id: usize) -> i64 {
+        assert!(self.nodes[node_id].is_root());
+        let diff_h = (self.nodes[parent_id].h + 1) - self.nodes[node_id].h;
+        self.shift(node_id, diff_h);
+        self.attach(node_id, parent_id);
+        diff_h * self.nodes[node_id].sum_A
+    }
+    // ノードを parent_id の子に付け替え、スコアの差分を返す (cut と link を続けるのと同じ)
+    // parent_id が node_id の子孫でないことは呼び出し側で確認する
+    pub fn reparent(&mut self, node_id: usize, parent_id: usize) -> i64 {
+        if !self.nodes[node_id].is_root() {
+            self.detach(node_id);
+        }
+        let diff_h = (self.nodes[parent_id].h + 1) - self.nodes[node_id].h;
+        self.shift(node_id, diff_h);
+        self.attach(node_id, parent_id);
+        diff_h * self.nodes[node_id].sum_A
+    }
+    // a が b の祖先 (b 自身を含む) かどうか
+    pub fn is_ancestor(&self, a: usize, b: usize) -> bool {
+        let mut id = b as i64;
+        while id >= 0 {
+            if id as usize == a {
+                return true;
+            }
+            id = self.nodes[id as usize].parent;
+        }
+        false
+    }
+    // 親の子から外し、元の祖先を更新する (部分木の深さはそのまま)
+    fn detach(&mut self, node_id: usize) {
+        let parent_id = self.nodes[node_id].parent as usize;
+        self.nodes[parent_id].children.retain(|&x| x != node_id);
+        self.nodes[node_id].parent = -1;
+        self.update_ancestors(parent_id, -self.nodes[node_id].sum_A);
+    }
+    // 深さを合わせた部分木を parent_id の子にし、新しい祖先を更新する
+    fn attach(&mut self, node_id: usize, parent_id: usize) {
+        self.nodes[node_id].parent = parent_id as i64;
+        self.nodes[parent_id].children.push(node_id);
+        self.update_ancestors(parent_id, self.nodes[node_id].sum_A);
+    }
+    // id とその祖先の sum_A に diff_A を足し、hmax を子から計算し直す
+    fn update_ancestors(&mut self, mut id: usize, diff_A: i64) {
+        // hmax が変わらなければ、それより上の hmax も変わらない
+        let mut fixed = false;
+        while id != !0 {
+            self.nodes[id].sum_A += diff_A;
+            if !fixed {
+                let hmax = self.nodes[id]
+                    .children
+                    .iter()
+                    .map(|&c| self.nodes[c].hmax)
+                    .max()
+                    .unwrap_or(self.nodes[id].h);
+                fixed = hmax == self.nodes[id].hmax;
+                self.nodes[id].hmax = hmax;
+            }
+            id = self.nodes[id].parent as usize;
+        }
+    }
+    // 部分木の深さを diff_h だけずらす
+    fn shift(&mut self, node_id: usize, diff_h: i64) {
+        if diff_h == 0 {
+            return;
+        }
+        let mut change_node_ids = vec![node_id];
+        while let Some(id) = change_node_ids.pop() {
+            self.nodes[id].h += diff_h;
+            self.nodes[id].hmax += diff_h;
+            change_node_ids.extend(self.nodes[id].children.iter().cloned());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+    use rand_pcg::Pcg64Mcg;
+
+    use super::Forest;
+
+    // 親の配列から h, sum_A, hmax を計算し直して比べる
+    fn check(forest: &Forest, A: &[i64]) {
+        let n = A.len();
+        for v in 0..n {
+            let mut h = 0;
+            let mut id = forest.nodes[v].parent;
+            while id >= 0 {
+                h += 1;
+                id = forest.nodes[id as usize].parent;
+            }
+            assert_eq!(forest.nodes[v].h, h);
+        }
+        for v in 0..n {
+            let (sum_A, hmax) = (0..n)
+                .filter(|&u| forest.is_ancestor(v, u))
+                .fold((0, 0), |(s, m), u| (s + A[u], m.max(forest.nodes[u].h)));
+            assert_eq!((forest.nodes[v].sum_A, forest.nodes[v].hmax), (sum_A, hmax));
+        }
+    }
+
+    #[test]
+    fn test_forest() {
+        let n = 30;
+        let mut rng = Pcg64Mcg::new(0);
+        let A = (0..n).map(|_| rng.gen_range(1..=100)).collect::<Vec<i64>>();
+        let mut forest = Forest::new(&A);
+        let mut score = A.iter().sum::<i64>();
+        for _ in 0..1000 {
+            let v = rng.gen_range(0..n);
+            let p = rng.gen_range(0..n);
+            match rng.gen_range(0..3) {
+                0 => score += forest.cut(v),
+                1 if forest.nodes[v].is_root() && !forest.is_ancestor(v, p) => {
+                    score += forest.link(v, p)
+                }
+                2 if !forest.is_ancestor(v, p) => score += forest.reparent(v, p),
+                _ => continue,
+            }
+            check(&forest, &A);
+            let expected = (0..n).map(|u| A[u] * (forest.nodes[u].h + 1)).sum::<i64>();
+            assert_eq!(score, expected);
+        }
+    }
+}
@@ -11,7 +11,11 @@ pub fn read_input() -> Input {
         UV: [(usize, usize); M],
         _XY: [(usize, usize); N],
     }
+    make_input(N, M, H, A, UV)
+}
 
+/// 隣接リストを美しさの昇順に並べた入力を作る
+pub fn make_input(N: usize, M: usize, H: usize, A: Vec<i64>, UV: Vec<(usize, usize)>) -> Input {
     let mut G = vec![vec![]; N];
     for (u, v) in UV {
         G[u].push((A[v], v));
@@ -2,17 +2,21 @@
 #![allow(dead_code)]
 
 use input::Input;
+use rand_pcg::Pcg64Mcg;
 
 use crate::{common::get_time, input::read_input};
 
 mod common;
+mod construct;
+mod exact;
+mod forest;
 mod input;
 mod state;
 mod test;
 
 fn solve(input: &Input) {
-    let mut state = state::State::new(input);
-    state.greedy(input);
+    let mut rng = Pcg64Mcg::new(0);
+    let mut state = construct::construct(input, &mut rng);
     state.annealing(input);
     eprintln!("Score = {}", state.score);
     state.output();
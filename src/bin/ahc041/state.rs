@@ -4,47 +4,23 @@ use itertools::Itertools;
 use rand::Rng;
 use rand_pcg::Pcg64Mcg;
 
-use crate::{common::get_time, input::Input};
-
-#[derive(Debug, Clone)]
-pub struct Node {
-    pub num: usize,
-    pub h: i64,
-    pub parent: i64,
-    pub children: Vec<usize>,
-    pub sum_A: i64,
-    pub hmax: i64,
-}
-
-impl Node {
-    pub fn is_root(&self) -> bool {
-        self.parent == -1
-    }
-    pub fn is_leaf(&self) -> bool {
-        self.children.is_empty()
-    }
-}
+use crate::{
+    common::get_time,
+    forest::{Forest, Node},
+    input::Input,
+};
 
 #[derive(Debug, Clone)]
 pub struct State {
     pub score: i64,
-    pub nodes: Vec<Node>,
+    pub forest: Forest,
 }
 
 impl State {
     pub fn new(input: &Input) -> Self {
         Self {
             score: input.A.iter().sum::<i64>() + 1,
-            nodes: (0..input.N)
-                .map(|i| Node {
-                    num: i,
-                    h: 0,
-                    parent: -1,
-                    children: vec![],
-                    sum_A: input.A[i] as i64,
-                    hmax: 0,
-                })
-                .collect(),
+            forest: Forest::new(&input.A),
         }
     }
     pub fn dfs(&mut self, input: &Input) {
@@ -124,70 +100,25 @@ impl State {
             );
         }
 
-        self.score = score;
-        for (c, p) in ans.iter().enumerate() {
-            if *p != -1 {
-                self.nodes[*p as usize].children.push(c);
-            }
-            self.nodes[c].parent = *p as i64;
-        }
-        for i in 0..input.N {
-            if self.nodes[i].is_root() {
-                let mut Q = vec![];
-                let mut leafs = vec![];
-                Q.push((i, 0));
-                while let Some((id, h)) = Q.pop() {
-                    self.nodes[id].h = h;
-                    if self.nodes[id].is_leaf() {
-                        leafs.push(id);
-                    }
-                    for child_id in self.nodes[id].children.iter() {
-                        Q.push((*child_id, h + 1));
-                    }
-                }
-
-                let mut Q = BinaryHeap::new();
-                let mut used = BTreeSet::new();
-                for id in leafs.iter() {
-                    self.nodes[*id].hmax = self.nodes[*id].h;
-                    self.nodes[*id].sum_A = input.A[*id] as i64;
-                    Q.push((self.nodes[*id].h, *id));
-                    used.insert(*id);
-                }
-                while let Some((_, id)) = Q.pop() {
-                    if !self.nodes[id].is_root() {
-                        let parent_id = self.nodes[id].parent as usize;
-                        self.nodes[parent_id].hmax =
-                            self.nodes[parent_id].hmax.max(self.nodes[id].hmax);
-                        self.nodes[parent_id].sum_A += self.nodes[id].sum_A;
-                        if used.contains(&parent_id) {
-                            continue;
-                        }
-                        used.insert(parent_id);
-                        Q.push((self.nodes[parent_id].h, parent_id));
-                    }
-                }
-            }
-        }
+        self.build(input, &ans, score);
     }
     pub fn greedy(&mut self, input: &Input) {
+        let order = (0..input.N)
+            .sorted_by_key(|&i| (input.A[i], i))
+            .collect_vec();
+        self.greedy_with_order(input, &order);
+    }
+    /// order の順に未使用の頂点を根にして、根からのパスとその先の幅優先探索で木を作る
+    pub fn greedy_with_order(&mut self, input: &Input, order: &[usize]) {
         let mut used = vec![false; input.N];
         let mut used_cnt = 0;
         let mut ans = vec![-1; input.N];
         let mut score = 1;
-        let mut order = input
-            .A
-            .iter()
-            .cloned()
-            .enumerate()
-            .map(|(i, a)| (a, i))
-            .collect_vec();
-        order.sort();
 
         while used_cnt < input.N {
             let root = {
                 let mut ret = !0;
-                for (_, i) in order.iter() {
+                for i in order.iter() {
                     if !used[*i] {
                         ret = *i;
                         break;
@@ -270,24 +201,63 @@ impl State {
             }
         }
 
+        self.build(input, &ans, score);
+    }
+    /// 美しさの昇順に未使用の頂点を根にして、深さ H まで幅優先探索で層を作る
+    pub fn bfs_layer(&mut self, input: &Input) {
+        let mut used = vec![false; input.N];
+        let mut ans = vec![-1; input.N];
+        let mut score = 1;
+        let order = (0..input.N)
+            .sorted_by_key(|&i| (input.A[i], i))
+            .collect_vec();
+
+        for &root in order.iter() {
+            if used[root] {
+                continue;
+            }
+            used[root] = true;
+            score += input.A[root];
+            let mut Q = VecDeque::new();
+            Q.push_back((root, 0));
+            while let Some((pos, h)) = Q.pop_front() {
+                if h == input.H {
+                    continue;
+                }
+                for &nxt in input.G[pos].iter() {
+                    if used[nxt] {
+                        continue;
+                    }
+                    used[nxt] = true;
+                    score += input.A[nxt] * (h as i64 + 2);
+                    ans[nxt] = pos as i32;
+                    Q.push_back((nxt, h + 1));
+                }
+            }
+        }
+
+        self.build(input, &ans, score);
+    }
+    // 親の配列から木を作り、深さ、部分木の最大の深さ、部分木の美しさの和を求める
+    fn build(&mut self, input: &Input, ans: &[i32], score: i64) {
         self.score = score;
         for (c, p) in ans.iter().enumerate() {
             if *p != -1 {
-                self.nodes[*p as usize].children.push(c);
+                self.forest.nodes[*p as usize].children.push(c);
             }
-            self.nodes[c].parent = *p as i64;
+            self.forest.nodes[c].parent = *p as i64;
         }
         for i in 0..input.N {
-            if self.nodes[i].is_root() {
+            if self.forest.nodes[i].is_root() {
                 let mut Q = vec![];
                 let mut leafs = vec![];
                 Q.push((i, 0));
                 while let Some((id, h)) = Q.pop() {
-                    self.nodes[id].h = h;
-                    if self.nodes[id].is_leaf() {
+                    self.forest.nodes[id].h = h;
+                    if self.forest.nodes[id].is_leaf() {
                         leafs.push(id);
                     }
-                    for child_id in self.nodes[id].children.iter() {
+                    for child_id in self.forest.nodes[id].children.iter() {
                         Q.push((*child_id, h + 1));
                     }
                 }
@@ -295,97 +265,65 @@ impl State {
                 let mut Q = BinaryHeap::new();
                 let mut used = BTreeSet::new();
                 for id in leafs.iter() {
-                    self.nodes[*id].hmax = self.nodes[*id].h;
-                    self.nodes[*id].sum_A = input.A[*id] as i64;
-                    Q.push((self.nodes[*id].h, *id));
+                    self.forest.nodes[*id].hmax = self.forest.nodes[*id].h;
+                    self.forest.nodes[*id].sum_A = input.A[*id] as i64;
+                    Q.push((self.forest.nodes[*id].h, *id));
                     used.insert(*id);
                 }
                 while let Some((_, id)) = Q.pop() {
-                    if !self.nodes[id].is_root() {
-                        let parent_id = self.nodes[id].parent as usize;
-                        self.nodes[parent_id].hmax =
-                            self.nodes[parent_id].hmax.max(self.nodes[id].hmax);
-                        self.nodes[parent_id].sum_A += self.nodes[id].sum_A;
+                    if !self.forest.nodes[id].is_root() {
+                        let parent_id = self.forest.nodes[id].parent as usize;
+                        self.forest.nodes[parent_id].hmax = self.forest.nodes[parent_id]
+                            .hmax
+                            .max(self.forest.nodes[id].hmax);
+                        self.forest.nodes[parent_id].sum_A += self.forest.nodes[id].sum_A;
                         if used.contains(&parent_id) {
                             continue;
                         }
                         used.insert(parent_id);
-                        Q.push((self.nodes[parent_id].h, parent_id));
+                        Q.push((self.forest.nodes[parent_id].h, parent_id));
                     }
                 }
             }
         }
     }
     pub fn annealing(&mut self, input: &Input) {
-        let tle = 1.99;
+        self.annealing_until(input, 1.99);
+    }
+    /// 現在時刻から tle まで焼きなます
+    pub fn annealing_until(&mut self, input: &Input, tle: f64) {
+        let start = get_time();
         let T0 = 200.0;
         let T1 = 0.01;
         let mut rng = Pcg64Mcg::new(100);
         let mut iter = 0;
         let mut valid_iter = 0;
         let mut update_iter = 0;
+        let mut temp = T0;
 
         loop {
-            if iter % 100 == 0 && get_time() > tle {
-                break;
+            // 時刻の確認と温度の更新は100回に1回
+            if iter % 100 == 0 {
+                let time = get_time();
+                if time > tle {
+                    break;
+                }
+                let progress = ((time - start) / (tle - start)).max(0.0).min(1.0);
+                temp = T0 + (T1 - T0) * progress;
             }
-            let node_id = rng.gen_range(0..self.nodes.len());
-            let neighbor_id = input.G[node_id][rng.gen_range(0..input.G[node_id].len())];
             iter += 1;
-            if !self.is_valid(&self.nodes[node_id], &self.nodes[neighbor_id], input) {
-                continue;
-            }
-            valid_iter += 1;
-            let diff_score = self.calc_diff_score(&self.nodes[node_id], &self.nodes[neighbor_id]);
-            let temp = T0 + (T1 - T0) * get_time() / tle;
-            if diff_score > 0 || rng.gen_bool((diff_score as f64 / temp).exp()) {
-                if self.is_loop(&self.nodes[node_id], &self.nodes[neighbor_id]) {
-                    continue;
-                }
-                update_iter += 1;
-                self.score += diff_score;
-
-                // 付け替え元の親とその祖先の更新
-                if !self.nodes[node_id].is_root() {
-                    let mut parent_id = self.nodes[node_id].parent as usize;
-                    // 付け替え元の親の子からnode_idを削除
-                    self.nodes[parent_id].children.retain(|&x| x != node_id);
-                    while parent_id != !0 {
-                        self.nodes[parent_id].sum_A -= self.nodes[node_id].sum_A;
-                        self.nodes[parent_id].hmax = self.nodes[parent_id]
-                            .children
-                            .iter()
-                            .map(|&id| self.nodes[id].hmax)
-                            .max()
-                            .unwrap_or(self.nodes[parent_id].h);
-                        parent_id = self.nodes[parent_id].parent as usize;
-                    }
-                }
-
-                // 付け替えるNodeとその子孫の更新
-                self.nodes[node_id].parent = neighbor_id as i64;
-                let diff_h = (self.nodes[neighbor_id].h + 1) - self.nodes[node_id].h;
-                let mut change_node_ids = vec![node_id];
-                while let Some(id) = change_node_ids.pop() {
-                    self.nodes[id].h += diff_h;
-                    self.nodes[id].hmax += diff_h;
-                    for child_id in self.nodes[id].children.iter() {
-                        change_node_ids.push(*child_id);
-                    }
-                }
-
-                // 付け替え先の親とその祖先の更新
-                self.nodes[neighbor_id].children.push(node_id);
-                let mut parent_id = neighbor_id;
-                while parent_id != !0 {
-                    self.nodes[parent_id].sum_A += self.nodes[node_id].sum_A;
-                    self.nodes[parent_id].hmax = self.nodes[parent_id]
-                        .children
-                        .iter()
-                        .map(|&id| self.nodes[id].hmax)
-                        .max()
-                        .unwrap_or(self.nodes[parent_id].h);
-                    parent_id = self.nodes[parent_id].parent as usize;
+            // 高さ制約を満たさない近傍は None
+            let accepted = match rng.gen_range(0..100) {
+                0..=79 => self.move_reparent(input, &mut rng, temp),
+                80..=84 => self.move_swap(input, &mut rng, temp),
+                85..=89 => self.move_promote(&mut rng, temp),
+                90..=94 => self.move_reroot(input, &mut rng, temp),
+                _ => self.move_rotate(input, &mut rng, temp),
+            };
+            if let Some(accepted) = accepted {
+                valid_iter += 1;
+                if accepted {
+                    update_iter += 1;
                 }
             }
         }
@@ -393,6 +331,178 @@ impl State {
         eprintln!("valid_iter = {}", valid_iter);
         eprintln!("update_iter = {}", update_iter);
     }
+    // ランダムなノードを隣接するノードの子に付け替える
+    fn move_reparent(&mut self, input: &Input, rng: &mut Pcg64Mcg, temp: f64) -> Option<bool> {
+        let node_id = rng.gen_range(0..self.forest.nodes.len());
+        let neighbor_id = input.G[node_id][rng.gen_range(0..input.G[node_id].len())];
+        if !self.is_valid(
+            &self.forest.nodes[node_id],
+            &self.forest.nodes[neighbor_id],
+            input,
+        ) {
+            return None;
+        }
+        let diff_score =
+            self.calc_diff_score(&self.forest.nodes[node_id], &self.forest.nodes[neighbor_id]);
+        if !accept(diff_score, temp, rng) {
+            return Some(false);
+        }
+        if self.forest.is_ancestor(node_id, neighbor_id) {
+            return None;
+        }
+        self.forest.reparent(node_id, neighbor_id);
+        self.score += diff_score;
+        Some(true)
+    }
+    // 2つの部分木の親を入れ替える
+    // node_id の新しい親を隣接ノードから選び、その子の1つと入れ替える
+    fn move_swap(&mut self, input: &Input, rng: &mut Pcg64Mcg, temp: f64) -> Option<bool> {
+        let u = rng.gen_range(0..self.forest.nodes.len());
+        let pv = input.G[u][rng.gen_range(0..input.G[u].len())];
+        if self.forest.nodes[pv].children.is_empty() {
+            return None;
+        }
+        let v =
+            self.forest.nodes[pv].children[rng.gen_range(0..self.forest.nodes[pv].children.len())];
+        let pu = self.forest.nodes[u].parent;
+        if v == u || pu == pv as i64 {
+            return None;
+        }
+        // v は u の元の親に隣接している必要がある (u が根なら v が根になる)
+        if pu >= 0 && !input.G[v].contains(&(pu as usize)) {
+            return None;
+        }
+        // 一方がもう一方の祖先だと閉路ができる
+        if self.forest.is_ancestor(u, pv) || (pu >= 0 && self.forest.is_ancestor(v, pu as usize)) {
+            return None;
+        }
+        let u_h = self.forest.nodes[pv].h + 1;
+        let v_h = if pu >= 0 {
+            self.forest.nodes[pu as usize].h + 1
+        } else {
+            0
+        };
+        let (nu, nv) = (&self.forest.nodes[u], &self.forest.nodes[v]);
+        if nu.hmax - nu.h + u_h > input.H as i64 || nv.hmax - nv.h + v_h > input.H as i64 {
+            return None;
+        }
+        let diff_score = (u_h - nu.h) * nu.sum_A + (v_h - nv.h) * nv.sum_A;
+        if !accept(diff_score, temp, rng) {
+            return Some(false);
+        }
+        self.forest.cut(u);
+        self.forest.cut(v);
+        self.forest.link(u, pv);
+        if pu >= 0 {
+            self.forest.link(v, pu as usize);
+        }
+        self.score += diff_score;
+        Some(true)
+    }
+    // ノードを親から切り離して根にする
+    fn move_promote(&mut self, rng: &mut Pcg64Mcg, temp: f64) -> Option<bool> {
+        let u = rng.gen_range(0..self.forest.nodes.len());
+        if self.forest.nodes[u].is_root() {
+            return None;
+        }
+        let diff_score = -self.forest.nodes[u].h * self.forest.nodes[u].sum_A;
+        if !accept(diff_score, temp, rng) {
+            return Some(false);
+        }
+        self.forest.cut(u);
+        self.score += diff_score;
+        Some(true)
+    }
+    // 木の根から u までのパスの向きを反転し、u を新しい根にする
+    // パスの i 番目 (u が 0 番目) のノードは深さ i になり、パス以外の子の部分木も一緒に動くので、
+    // 高さとスコアの差分はパスをたどって計算し、採用するときだけ付け替える
+    fn move_reroot(&mut self, input: &Input, rng: &mut Pcg64Mcg, temp: f64) -> Option<bool> {
+        let u = rng.gen_range(0..self.forest.nodes.len());
+        if self.forest.nodes[u].is_root() {
+            return None;
+        }
+        let mut path = vec![u];
+        while !self.forest.nodes[*path.last().unwrap()].is_root() {
+            path.push(self.forest.nodes[*path.last().unwrap()].parent as usize);
+        }
+        let mut hmax = self.forest.nodes[u].hmax - self.forest.nodes[u].h;
+        let mut diff_score = 0;
+        for (i, &p) in path.iter().enumerate() {
+            let node = &self.forest.nodes[p];
+            let (own_A, own_hmax) = if i == 0 {
+                (node.sum_A, hmax)
+            } else {
+                let prev = path[i - 1];
+                let h = node
+                    .children
+                    .iter()
+                    .filter(|&&id| id != prev)
+                    .map(|&id| self.forest.nodes[id].hmax)
+                    .max()
+                    .unwrap_or(node.h);
+                (
+                    node.sum_A - self.forest.nodes[prev].sum_A,
+                    h - node.h + i as i64,
+                )
+            };
+            hmax = hmax.max(own_hmax);
+            diff_score += (i as i64 - node.h) * own_A;
+        }
+        if hmax > input.H as i64 {
+            return None;
+        }
+        if !accept(diff_score, temp, rng) {
+            return Some(false);
+        }
+        let before = self.score;
+        self.reverse_path(&path);
+        debug_assert_eq!(self.score, before + diff_score);
+        Some(true)
+    }
+    // path[0] が子孫、path[last] が根のパスを反転して path[0] を根にする
+    fn reverse_path(&mut self, path: &[usize]) {
+        self.score += self.forest.cut(path[0]);
+        for i in 1..path.len() {
+            self.score += self.forest.cut(path[i]);
+            self.score += self.forest.link(path[i], path[i - 1]);
+        }
+    }
+    // 子 c と親 u を入れ替え、鎖を1段ずらす
+    // c は u の元の位置に入り、u は c の子になる (それぞれの他の子はそのまま)
+    fn move_rotate(&mut self, input: &Input, rng: &mut Pcg64Mcg, temp: f64) -> Option<bool> {
+        let c = rng.gen_range(0..self.forest.nodes.len());
+        if self.forest.nodes[c].is_root() {
+            return None;
+        }
+        let u = self.forest.nodes[c].parent as usize;
+        let pu = self.forest.nodes[u].parent;
+        if pu >= 0 && !input.G[c].contains(&(pu as usize)) {
+            return None;
+        }
+        // u とその c 以外の部分木は1段深くなる
+        let other_hmax = self.forest.nodes[u]
+            .children
+            .iter()
+            .filter(|&&id| id != c)
+            .map(|&id| self.forest.nodes[id].hmax)
+            .max()
+            .unwrap_or(self.forest.nodes[u].h);
+        if other_hmax + 1 > input.H as i64 {
+            return None;
+        }
+        let diff_score = self.forest.nodes[u].sum_A - 2 * self.forest.nodes[c].sum_A;
+        if !accept(diff_score, temp, rng) {
+            return Some(false);
+        }
+        self.forest.cut(c);
+        self.forest.cut(u);
+        if pu >= 0 {
+            self.forest.link(c, pu as usize);
+        }
+        self.forest.link(u, c);
+        self.score += diff_score;
+        Some(true)
+    }
     pub fn is_valid(&self, child: &Node, parent: &Node, input: &Input) -> bool {
         // 親の変更なし
         if child.parent == parent.num as i64 {
@@ -404,19 +514,6 @@ impl State {
         }
         true
     }
-    pub fn is_loop(&self, child: &Node, parent: &Node) -> bool {
-        let mut Q = VecDeque::new();
-        Q.push_back(child.num);
-        while let Some(id) = Q.pop_front() {
-            if parent.num == id {
-                return true;
-            }
-            for child_id in self.nodes[id].children.iter() {
-                Q.push_back(*child_id);
-            }
-        }
-        false
-    }
     pub fn calc_diff_score(&self, child: &Node, parent: &Node) -> i64 {
         let diff_h = (parent.h + 1) - child.h;
         diff_h * child.sum_A as i64
@@ -424,6 +521,7 @@ impl State {
     pub fn output(&self) {
         // eprintln!("Score = {}", self.score);
         let ans = self
+            .forest
             .nodes
             .iter()
             .map(|node| node.parent)
@@ -432,6 +530,14 @@ impl State {
     }
 }
 
+fn accept(diff_score: i64, temp: f64, rng: &mut Pcg64Mcg) -> bool {
+    if diff_score >= 0 {
+        return true;
+    }
+    // 温度が0以下なら改悪は受理しない
+    temp > 0.0 && rng.gen_bool((diff_score as f64 / temp).exp().min(1.0))
+}
+
 fn dfs(
     pos: usize,
     h: usize,
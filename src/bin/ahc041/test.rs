@@ -207,4 +207,67 @@ mod tests {
         println!("{}", total);
         writeln!(file, "{}", total).unwrap();
     }
+
+    // 付け替えの速度比較
+    // 以前の State: 部分木の幅優先探索で閉路を判定し、cut と link で部分木を2回たどる
+    // Forest::reparent: 付け替え先の祖先で閉路を判定し、部分木を1回だけたどる
+    // exp: makers stest ahc041 bench_forest
+    #[test]
+    fn bench_forest() {
+        use crate::forest::Forest;
+        use rand::Rng;
+        use rand_pcg::Pcg64Mcg;
+        use std::collections::VecDeque;
+        use std::time::Instant;
+
+        let n = 1000;
+        let H = 10;
+        let op_num = 200000;
+        let mut rng = Pcg64Mcg::new(0);
+        let A = (0..n).map(|_| rng.gen_range(1..=100)).collect::<Vec<i64>>();
+
+        // 高さ制約を満たす付け替えの列を作る
+        let mut forest = Forest::new(&A);
+        let mut ops = vec![];
+        while ops.len() < op_num {
+            let v = rng.gen_range(0..n);
+            let p = rng.gen_range(0..n);
+            let node = &forest.nodes[v];
+            if forest.is_ancestor(v, p) || node.hmax - node.h + forest.nodes[p].h + 1 > H {
+                continue;
+            }
+            forest.reparent(v, p);
+            ops.push((v, p));
+        }
+
+        let mut old = Forest::new(&A);
+        let start = Instant::now();
+        for &(v, p) in ops.iter() {
+            let mut Q = VecDeque::from(vec![v]);
+            while let Some(id) = Q.pop_front() {
+                assert_ne!(id, p);
+                Q.extend(old.nodes[id].children.iter().cloned());
+            }
+            old.cut(v);
+            old.link(v, p);
+        }
+        let old_time = start.elapsed().as_secs_f64();
+
+        let mut new = Forest::new(&A);
+        let start = Instant::now();
+        for &(v, p) in ops.iter() {
+            assert!(!new.is_ancestor(v, p));
+            new.reparent(v, p);
+        }
+        let new_time = start.elapsed().as_secs_f64();
+
+        for v in 0..n {
+            let (a, b) = (&old.nodes[v], &new.nodes[v]);
+            assert_eq!((a.h, a.sum_A, a.hmax), (b.h, b.sum_A, b.hmax));
+        }
+        println!(
+            "ops = {}, cut + link = {:.3}s, reparent = {:.3}s",
+            op_num, old_time, new_time
+        );
+    }
 }
@@ -3,10 +3,16 @@ use std::cmp::Reverse;
 use rustc_hash::FxHashSet;
 
 use crate::{
+    common::get_time,
     input::Input,
-    state::{Op, State},
+    state::{max_turn, Op, State},
+    trajectory::Trajectory,
 };
 
+// 候補は操作後のターン数ごとのバケットに入れ、同じターン数のノードどうしでスコアを比べる
+// 先のターンのバケットがこの倍率 * width を超えたら、上位 width 個に絞る
+const PRUNE_RATIO: usize = 4;
+
 #[derive(Debug, PartialEq, Eq)]
 pub enum ScoreOrder {
     Ascending,  // Lower is better
@@ -25,71 +31,88 @@ impl Node {
         ret
     }
     fn apply(&mut self, cand: &Cand, input: &Input) {
-        self.state
-            .apply(cand.eval_score, cand.hash, &cand.op, input);
+        self.state.apply(cand.score, cand.hash, &cand.op, input);
     }
 }
 
 #[derive(Debug, Clone)]
 struct Cand {
     op: Op,
-    parent: usize,
-    eval_score: i64,
+    parent: (usize, usize), // (ターン数, そのターンのノードの番号)
+    score: i64,
     hash: usize,
     is_done: bool,
 }
-impl Cand {
-    fn raw_score(&self, _input: &Input) -> i64 {
-        self.eval_score
-    }
-}
 
 #[derive(Debug)]
 pub struct BeamSearch {
     track: Vec<(usize, Op)>,
-    nodes: Vec<Node>,
-    next_nodes: Vec<Node>,
+    layers: Vec<Vec<Node>>,  // ターン数ごとに選ばれたノード
+    buckets: Vec<Vec<Cand>>, // 操作後のターン数ごとの候補
+    pub trajectory: Trajectory,
 }
 impl BeamSearch {
     pub fn new(node: Node) -> BeamSearch {
         BeamSearch {
-            nodes: vec![node],
+            layers: vec![vec![node]],
             track: vec![],
-            next_nodes: vec![],
+            buckets: vec![],
+            trajectory: Trajectory::new(0.0),
         }
     }
 
+    // ターン t のノードから候補を作り、操作後のターン数のバケットに入れる
     #[allow(unused_variables)]
-    fn append_cands(&self, input: &Input, cands: &mut Vec<Cand>, rng: &mut rand_pcg::Pcg64Mcg) {
-        for parent_idx in 0..self.nodes.len() {
-            let parent_node = &self.nodes[parent_idx];
+    fn append_cands(
+        &mut self,
+        t: usize,
+        depth: usize,
+        width: usize,
+        input: &Input,
+        score_order: &ScoreOrder,
+        rng: &mut rand_pcg::Pcg64Mcg,
+    ) {
+        let max_turn = depth.min(max_turn(input.N));
+        if self.buckets.len() <= max_turn {
+            self.buckets.resize(max_turn + 1, vec![]);
+        }
+        for parent_idx in 0..self.layers[t].len() {
+            let parent_node = &self.layers[t][parent_idx];
             for (score, hash, op, is_done) in parent_node.state.cand(input) {
-                let cand = Cand {
+                let turn = t + op.turn();
+                if turn > max_turn {
+                    continue;
+                }
+                self.buckets[turn].push(Cand {
                     op,
-                    parent: parent_idx,
-                    eval_score: score,
+                    parent: (t, parent_idx),
+                    score,
                     hash,
                     is_done,
-                };
-                cands.push(cand);
+                });
+            }
+        }
+        // 次のターンのバケットはすぐに絞るので、それより後のバケットだけ絞る
+        for turn in t + 2..=max_turn.min(t + 2 * input.N) {
+            if self.buckets[turn].len() > PRUNE_RATIO * width {
+                select(&mut self.buckets[turn], width, score_order);
             }
         }
     }
 
-    fn update<I: Iterator<Item = Cand>>(&mut self, cands: I, input: &Input) {
-        self.next_nodes.clear();
+    fn update<I: Iterator<Item = Cand>>(&mut self, cands: I, input: &Input) -> Vec<Node> {
+        let mut next_nodes = vec![];
         for cand in cands {
-            let parent_node = &self.nodes[cand.parent];
+            let parent_node = &self.layers[cand.parent.0][cand.parent.1];
             let mut new_node = parent_node.new_node(&cand, input);
             self.track.push((parent_node.track_id, cand.op));
             new_node.track_id = self.track.len() - 1;
-            self.next_nodes.push(new_node);
+            next_nodes.push(new_node);
         }
-        std::mem::swap(&mut self.nodes, &mut self.next_nodes);
+        next_nodes
     }
 
     fn restore(&self, mut idx: usize) -> Vec<Op> {
-        idx = self.nodes[idx].track_id;
         let mut ret = vec![];
         while idx != !0 {
             ret.push(self.track[idx].1.clone());
@@ -99,6 +122,7 @@ impl BeamSearch {
         ret
     }
 
+    /// depth はターン数の上限
     pub fn solve(
         &mut self,
         width: usize,
@@ -106,41 +130,61 @@ impl BeamSearch {
         input: &Input,
         score_order: ScoreOrder,
     ) -> Vec<Op> {
-        let mut cands = Vec::<Cand>::new();
-        let mut set = FxHashSet::default();
         let mut rng = rand_pcg::Pcg64Mcg::new(0);
-        for t in 0..depth {
+        // 最後に候補があったターンの最良の候補
+        let mut best: Option<Cand> = None;
+        for t in 0..=depth.min(max_turn(input.N)) {
             if t != 0 {
-                if score_order == ScoreOrder::Ascending {
-                    cands.sort_unstable_by_key(|a| a.eval_score);
-                } else {
-                    cands.sort_unstable_by_key(|a| Reverse(a.eval_score));
+                let mut cands = std::mem::take(&mut self.buckets[t]);
+                if cands.is_empty() {
+                    self.layers.push(vec![]);
+                    continue;
                 }
+                let cand_num = cands.len();
+                select(&mut cands, width, &score_order);
                 let best_cand = &cands[0];
+                self.trajectory.push_layer(
+                    get_time(),
+                    t,
+                    best_cand.score,
+                    cands.last().unwrap().score,
+                    cands.len(),
+                    cand_num,
+                );
+                best = Some(best_cand.clone());
                 if best_cand.is_done {
                     break;
                 }
-                set.clear();
-                self.update(
-                    cands
-                        .iter()
-                        .filter(|cand| set.insert(cand.hash))
-                        .take(width)
-                        .cloned(),
-                    input,
-                );
+                let nodes = self.update(cands.iter().cloned(), input);
+                self.layers.push(nodes);
+                // 次のターンのバケットに領域を使い回す
+                if t + 1 < self.buckets.len() {
+                    cands.clear();
+                    cands.append(&mut self.buckets[t + 1]);
+                    self.buckets[t + 1] = cands;
+                }
+                // マクロ操作でも 2(N-1) ターンより前のノードは親にならない
+                if t >= 2 * input.N {
+                    self.layers[t - 2 * input.N].clear();
+                }
             }
-            cands.clear();
-            self.append_cands(input, &mut cands, &mut rng);
+            self.append_cands(t, depth, width, input, &score_order, &mut rng);
         }
 
-        let best = if score_order == ScoreOrder::Ascending {
-            cands.iter().min_by_key(|a| a.raw_score(input)).unwrap()
-        } else {
-            cands.iter().max_by_key(|a| a.raw_score(input)).unwrap()
-        };
-        let mut ret = self.restore(best.parent);
+        let best = best.unwrap();
+        let mut ret = self.restore(self.layers[best.parent.0][best.parent.1].track_id);
         ret.push(best.op.clone());
         ret
     }
 }
+
+// 良い順に並べ、同じ盤面を除いて上位 width 個に絞る
+fn select(cands: &mut Vec<Cand>, width: usize, score_order: &ScoreOrder) {
+    if *score_order == ScoreOrder::Ascending {
+        cands.sort_unstable_by_key(|a| a.score);
+    } else {
+        cands.sort_unstable_by_key(|a| Reverse(a.score));
+    }
+    let mut set = FxHashSet::default();
+    cands.retain(|cand| set.len() < width && set.insert(cand.hash));
+}
@@ -12,6 +12,8 @@ mod hash;
 mod input;
 mod state;
 mod test;
+#[path = "../utils/trajectory.rs"]
+mod trajectory;
 
 fn solve(input: &Input) {
     let init_state = state::State::new(input);
@@ -20,9 +22,10 @@ fn solve(input: &Input) {
         state: init_state,
     };
     let mut beam = beam::BeamSearch::new(init_node);
-    let width = 2000;
+    let width = 1500;
     let ops = beam.solve(width, 200, &input, beam::ScoreOrder::Ascending);
-    for op in ops.iter() {
+    beam.trajectory.save();
+    for op in ops.iter().flat_map(|op| op.expand()) {
         println!("{} {}", op.dir, op.idx);
     }
 }
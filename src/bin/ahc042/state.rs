@@ -1,43 +1,125 @@
-use std::collections::BTreeSet;
-
 use crate::input::Input;
 
+// 鬼から壁までの間に福があるときに距離に加える罰則
+const BLOCK_PENALTY: i64 = 1;
+
+// ターン数の上限
+pub fn max_turn(N: usize) -> usize {
+    4 * N * N
+}
+
+/// k > 0 のときは、dir の向きに k 回ずらしてから逆向きに k 回ずらして戻すマクロ操作
 #[derive(Debug, Clone, Copy)]
 pub struct Op {
     pub dir: char,
     pub idx: usize,
+    pub k: usize,
+}
+
+impl Op {
+    /// 操作にかかるターン数
+    pub fn turn(&self) -> usize {
+        if self.k == 0 {
+            1
+        } else {
+            2 * self.k
+        }
+    }
+    /// 1回ずつのずらし操作に展開する
+    pub fn expand(&self) -> Vec<Op> {
+        let single = |dir| Op {
+            dir,
+            idx: self.idx,
+            k: 0,
+        };
+        if self.k == 0 {
+            return vec![*self];
+        }
+        let mut ret = vec![single(self.dir); self.k];
+        ret.extend(vec![single(reverse(self.dir)); self.k]);
+        ret
+    }
+}
+
+fn reverse(dir: char) -> char {
+    match dir {
+        'L' => 'R',
+        'R' => 'L',
+        'U' => 'D',
+        'D' => 'U',
+        _ => unreachable!(),
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct State {
     pub N: usize,
     pub field: Vec<Vec<char>>,
-    pub score: i64, // 各鬼から壁までの距離の総和(最小化)
+    pub score: i64, // 各鬼から壁までの距離の総和(最小化)。福に塞がれた向きは罰則を加える
     pub hash: usize,
-    pub x_positions: BTreeSet<(usize, usize)>,
+    pub turn: usize,
+    row_o: Vec<u64>, // 各行の福のある列のビット集合
+    col_o: Vec<u64>, // 各列の福のある行のビット集合
+    row_x: Vec<u64>, // 各行の鬼のいる列のビット集合
+    col_x: Vec<u64>, // 各列の鬼のいる行のビット集合
 }
 
 impl State {
     pub fn new(input: &Input) -> Self {
-        let mut x_positions = BTreeSet::new();
-        let mut score = 0;
-        let mut hash = 0;
+        let mut state = Self {
+            N: input.N,
+            field: input.C.clone(),
+            score: 0,
+            hash: 0,
+            turn: 0,
+            row_o: vec![],
+            col_o: vec![],
+            row_x: vec![],
+            col_x: vec![],
+        };
+        state.build();
         for i in 0..input.N {
-            for j in 0..input.N {
-                if input.C[i][j] == 'x' {
-                    x_positions.insert((i, j));
-                    score += calc_dist(i, j, input.N);
-                    hash ^= input.calc_hash.hash_map[i][j];
-                }
+            for j in bits(state.row_x[i]) {
+                state.score += state.x_dist(i, j, blocked(state.row_o[i], state.col_o[j], i, j));
+                state.hash ^= input.calc_hash.hash_map[i][j];
             }
         }
-        Self {
-            N: input.N,
-            field: input.C.clone(),
-            score,
-            hash,
-            x_positions,
+        state
+    }
+    // 福と鬼の位置のビット集合を作り直す
+    fn build(&mut self) {
+        let N = self.N;
+        for set in [
+            &mut self.row_o,
+            &mut self.col_o,
+            &mut self.row_x,
+            &mut self.col_x,
+        ] {
+            set.clear();
+            set.resize(N, 0);
         }
+        for i in 0..N {
+            for j in 0..N {
+                if self.field[i][j] == 'o' {
+                    self.row_o[i] |= 1 << j;
+                    self.col_o[j] |= 1 << i;
+                } else if self.field[i][j] == 'x' {
+                    self.row_x[i] |= 1 << j;
+                    self.col_x[j] |= 1 << i;
+                }
+            }
+        }
+    }
+    // (row, col) の鬼から壁までの距離
+    // 福に塞がれた向きは、その福をどかす手間として BLOCK_PENALTY を加える
+    fn x_dist(&self, row: usize, col: usize, blocked: [bool; 4]) -> i64 {
+        let N = self.N as i64;
+        let (row, col) = (row as i64, col as i64);
+        let dist = |d: i64, b: bool| if b { d + BLOCK_PENALTY } else { d };
+        dist(col + 1, blocked[0])
+            .min(dist(N - col, blocked[1]))
+            .min(dist(row + 1, blocked[2]))
+            .min(dist(N - row, blocked[3]))
     }
     pub fn can_shift(&self, dir: char, idx: usize) -> bool {
         match dir {
@@ -49,102 +131,136 @@ impl State {
         }
     }
     pub fn cand(&self, input: &Input) -> Vec<(i64, usize, Op, bool)> {
-        let mut cand = vec![];
-
-        for i in 0..self.N {
-            if self.can_shift('L', i) {
-                let mut prev = 0;
-                let mut next = 0;
-                let mut next_hash = self.hash;
-                for &(row, col) in self.x_positions.iter() {
-                    if row == i {
-                        prev += calc_dist(row, col, self.N);
-                        if col > 0 {
-                            next += calc_dist(row, col - 1, self.N);
-                        }
-                        next_hash = input.calc_hash.calc(next_hash, row, col, 'L');
-                    }
+        let mut cand = Vec::with_capacity(8 * self.N);
+        for dir in ['L', 'R', 'U', 'D'] {
+            for idx in 0..self.N {
+                if self.can_shift(dir, idx) {
+                    let (next_score, next_hash) = self.shift_score(input, dir, idx);
+                    cand.push((
+                        next_score,
+                        next_hash,
+                        Op { dir, idx, k: 0 },
+                        next_score == 0,
+                    ));
                 }
-                let diff = next - prev;
-                let next_score = self.score + diff;
-                cand.push((
-                    next_score,
-                    next_hash,
-                    Op { dir: 'L', idx: i },
-                    next_score == 0,
-                ));
             }
-            if self.can_shift('R', i) {
-                let mut prev = 0;
-                let mut next = 0;
-                let mut next_hash = self.hash;
-                for &(row, col) in self.x_positions.iter() {
-                    if row == i {
-                        prev += calc_dist(row, col, self.N);
-                        if col + 1 < self.N {
-                            next += calc_dist(row, col + 1, self.N);
-                        }
-                        next_hash = input.calc_hash.calc(next_hash, row, col, 'R');
-                    }
+        }
+        self.append_macro_cands(input, &mut cand);
+        cand
+    }
+    // dir の向きに idx の列を1回ずらした後のスコアとハッシュ
+    // 距離が変わるのは、ずらした列の鬼と、福の有無が変わったマスを通る列の鬼だけ
+    fn shift_score(&self, input: &Input, dir: char, idx: usize) -> (i64, usize) {
+        let horizontal = dir == 'L' || dir == 'R';
+        let (line_o, line_x) = if horizontal {
+            (self.row_o[idx], self.row_x[idx])
+        } else {
+            (self.col_o[idx], self.col_x[idx])
+        };
+        // 端の福は落とせないので、ずらした後の福の位置はずらす前のビット集合をずらしたもの
+        let next_o = match dir {
+            'L' | 'U' => line_o >> 1,
+            _ => line_o << 1,
+        };
+        let mut score = self.score;
+        let mut hash = self.hash;
+        for q in bits(line_x) {
+            let (row, col) = if horizontal { (idx, q) } else { (q, idx) };
+            score -= self.x_dist(
+                row,
+                col,
+                blocked(self.row_o[row], self.col_o[col], row, col),
+            );
+            hash = input.calc_hash.calc(hash, row, col, dir);
+            let (nr, nc) = match dir {
+                'L' if col > 0 => (row, col - 1),
+                'R' if col + 1 < self.N => (row, col + 1),
+                'U' if row > 0 => (row - 1, col),
+                'D' if row + 1 < self.N => (row + 1, col),
+                _ => continue,
+            };
+            // 交差する列では、ずらした列のマスは鬼なので福から除く
+            let next = if horizontal {
+                blocked(next_o, self.col_o[nc] & !(1 << idx), nr, nc)
+            } else {
+                blocked(self.row_o[nr] & !(1 << idx), next_o, nr, nc)
+            };
+            score += self.x_dist(nr, nc, next);
+        }
+        for q in bits(line_o ^ next_o) {
+            // q を通る交差する列の鬼
+            let (cross_o, cross_x) = if horizontal {
+                (self.col_o[q], self.col_x[q])
+            } else {
+                (self.row_o[q], self.row_x[q])
+            };
+            let next_cross_o = cross_o ^ 1 << idx;
+            for y in bits(cross_x & !(1 << idx)) {
+                let (row, col) = if horizontal { (y, q) } else { (q, y) };
+                let (prev, next) = if horizontal {
+                    (
+                        blocked(self.row_o[row], cross_o, row, col),
+                        blocked(self.row_o[row], next_cross_o, row, col),
+                    )
+                } else {
+                    (
+                        blocked(cross_o, self.col_o[col], row, col),
+                        blocked(next_cross_o, self.col_o[col], row, col),
+                    )
+                };
+                if prev != next {
+                    score += self.x_dist(row, col, next) - self.x_dist(row, col, prev);
                 }
-                let diff = next - prev;
-                let next_score = self.score + diff;
-                cand.push((
-                    next_score,
-                    next_hash,
-                    Op { dir: 'R', idx: i },
-                    next_score == 0,
-                ));
             }
         }
-
-        for j in 0..self.N {
-            if self.can_shift('U', j) {
-                let mut prev = 0;
-                let mut next = 0;
-                let mut next_hash = self.hash;
-                for &(row, col) in self.x_positions.iter() {
-                    if col == j {
-                        prev += calc_dist(row, col, self.N);
-                        if row > 0 {
-                            next += calc_dist(row - 1, col, self.N);
-                        }
-                        next_hash = input.calc_hash.calc(next_hash, row, col, 'U');
-                    }
+        (score, hash)
+    }
+    // k 回ずらしてから戻すマクロ操作の候補
+    // 端から k マスに福がなければ、そのマスの鬼だけが取り除かれ、それ以外は元の位置に戻る
+    // k を端から最も近い福の位置とすると、1回ずつ k 回ずらした盤面ではその福が端に来て、その向きにはずらせなくなる
+    // 取り除く鬼が他の3方向を福に塞がれていれば、1回ずつのずらし操作ではその鬼を取り除きつつ福を元に戻せないので、そのときだけ候補にする
+    fn append_macro_cands(&self, input: &Input, cand: &mut Vec<(i64, usize, Op, bool)>) {
+        for (d, dir) in ['L', 'R', 'U', 'D'].into_iter().enumerate() {
+            for idx in 0..self.N {
+                let line_o = if d < 2 {
+                    self.row_o[idx]
+                } else {
+                    self.col_o[idx]
+                };
+                if line_o == 0 {
+                    continue;
                 }
-                let diff = next - prev;
-                let next_score = self.score + diff;
-                cand.push((
-                    next_score,
-                    next_hash,
-                    Op { dir: 'U', idx: j },
-                    next_score == 0,
-                ));
-            }
-            if self.can_shift('D', j) {
-                let mut prev = 0;
-                let mut next = 0;
+                let k = if d % 2 == 0 {
+                    line_o.trailing_zeros() as usize
+                } else {
+                    self.N + line_o.leading_zeros() as usize - 64
+                };
+                // 端から k マスの鬼
+                let line_x = if d < 2 {
+                    self.row_x[idx]
+                } else {
+                    self.col_x[idx]
+                };
+                let edge_x = if d % 2 == 0 {
+                    line_x & ((1 << k) - 1)
+                } else {
+                    line_x >> (self.N - k) << (self.N - k)
+                };
+                let mut next_score = self.score;
                 let mut next_hash = self.hash;
-                for &(row, col) in self.x_positions.iter() {
-                    if col == j {
-                        prev += calc_dist(row, col, self.N);
-                        if row + 1 < self.N {
-                            next += calc_dist(row + 1, col, self.N);
-                        }
-                        next_hash = input.calc_hash.calc(next_hash, row, col, 'D');
-                    }
+                let mut trapped = false;
+                for q in bits(edge_x) {
+                    let (row, col) = if d < 2 { (idx, q) } else { (q, idx) };
+                    let blocked = blocked(self.row_o[row], self.col_o[col], row, col);
+                    trapped |= (0..4).all(|e| e == d || blocked[e]);
+                    next_score -= self.x_dist(row, col, blocked);
+                    next_hash ^= input.calc_hash.hash_map[row][col];
+                }
+                if trapped {
+                    cand.push((next_score, next_hash, Op { dir, idx, k }, next_score == 0));
                 }
-                let diff = next - prev;
-                let next_score = self.score + diff;
-                cand.push((
-                    next_score,
-                    next_hash,
-                    Op { dir: 'D', idx: j },
-                    next_score == 0,
-                ));
             }
         }
-        cand
     }
     pub fn shift(&mut self, op: &Op) {
         if op.dir == 'L' {
@@ -172,19 +288,130 @@ impl State {
     pub fn apply(&mut self, score: i64, hash: usize, op: &Op, _input: &Input) {
         self.score = score;
         self.hash = hash;
-        self.shift(&op);
-        self.x_positions.clear();
+        self.turn += op.turn();
+        for single in op.expand() {
+            self.shift(&single);
+        }
+        self.build();
+    }
+}
 
-        for i in 0..self.N {
-            for j in 0..self.N {
-                if self.field[i][j] == 'x' {
-                    self.x_positions.insert((i, j));
-                }
+// (row, col) から 'L', 'R', 'U', 'D' の向きの壁までの間に福があるか
+// row_o, col_o はその行と列の福のビット集合
+fn blocked(row_o: u64, col_o: u64, row: usize, col: usize) -> [bool; 4] {
+    [
+        row_o & ((1 << col) - 1) != 0,
+        row_o >> (col + 1) != 0,
+        col_o & ((1 << row) - 1) != 0,
+        col_o >> (row + 1) != 0,
+    ]
+}
+
+// ビット集合に含まれる要素を小さい順に返す
+fn bits(mut set: u64) -> impl Iterator<Item = usize> {
+    std::iter::from_fn(move || {
+        if set == 0 {
+            return None;
+        }
+        let i = set.trailing_zeros() as usize;
+        set &= set - 1;
+        Some(i)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{State, BLOCK_PENALTY};
+    use crate::{hash::CalcHash, input::Input};
+
+    // 候補のスコアとハッシュが、操作を適用した盤面から計算し直したものと一致するか確認する
+    // マクロ操作の数と、各候補を適用した盤面を返す
+    fn check_cands(input: &Input, state: &State) -> (usize, Vec<State>) {
+        let N = input.N;
+        let mut macro_num = 0;
+        let mut nexts = vec![];
+        for (score, hash, op, _) in state.cand(input) {
+            if op.k > 0 {
+                macro_num += 1;
             }
+            let mut next = state.clone();
+            next.apply(score, hash, &op, input);
+            let expected = State::new(&Input {
+                N,
+                C: next.field.clone(),
+                calc_hash: CalcHash::new(N),
+            });
+            assert_eq!(next.score, expected.score, "{:?}", op);
+            assert_eq!(next.hash, expected.hash, "{:?}", op);
+            assert_eq!(next.turn, state.turn + op.turn());
+            // 福は盤面に残る
+            let o_num = |s: &State| s.field.iter().flatten().filter(|&&c| c == 'o').count();
+            assert_eq!(o_num(&next), o_num(state));
+            nexts.push(next);
+        }
+        (macro_num, nexts)
+    }
+
+    #[test]
+    fn test_cands() {
+        // (2, 1) の鬼は右・上・下を福に塞がれていて、左に2回ずらしてから戻すマクロ操作だけが候補になる
+        let C = [".o..x.", "......", ".xo.x.", "x.....", ".o....", "...x.."];
+        let N = C.len();
+        let input = Input {
+            N,
+            C: C.iter().map(|s| s.chars().collect()).collect(),
+            calc_hash: CalcHash::new(N),
+        };
+        let (macro_num, _) = check_cands(&input, &State::new(&input));
+        assert_eq!(macro_num, 1);
+        let ops = State::new(&input).cand(&input);
+        let op = ops.iter().map(|c| c.2).find(|op| op.k > 0).unwrap();
+        assert_eq!((op.dir, op.idx, op.k), ('L', 2, 2));
+
+        // 10x10 の盤面で操作を続けても一致する
+        let N = 10;
+        let C = (0..N)
+            .map(|i| {
+                (0..N)
+                    .map(|j| match (i * 7 + j * 13) % 11 {
+                        0 | 5 => 'x',
+                        3 => 'o',
+                        _ => '.',
+                    })
+                    .collect()
+            })
+            .collect();
+        let input = Input {
+            N,
+            C,
+            calc_hash: CalcHash::new(N),
+        };
+        let mut state = State::new(&input);
+        for step in 0..30 {
+            let (_, nexts) = check_cands(&input, &state);
+            state = nexts[step * 7 % nexts.len()].clone();
         }
     }
-}
 
-fn calc_dist(row: usize, col: usize, N: usize) -> i64 {
-    (row + 1).min(N - row).min(col + 1).min(N - col) as i64
+    #[test]
+    fn test_block_penalty() {
+        // 壁までの間に福がある向きは、罰則を加えた距離で数える
+        let C = [".....", "o.x..", ".....", ".....", "....."];
+        let N = C.len();
+        let input = Input {
+            N,
+            C: C.iter().map(|s| s.chars().collect()).collect(),
+            calc_hash: CalcHash::new(N),
+        };
+        let state = State::new(&input);
+        // 左は福に塞がれて 3 + BLOCK_PENALTY、上と右は 2, 3 なので上の 2
+        assert_eq!(state.score, 2);
+        let C = ["..o..", "o.x..", ".....", ".....", "....."];
+        let input = Input {
+            N,
+            C: C.iter().map(|s| s.chars().collect()).collect(),
+            calc_hash: CalcHash::new(N),
+        };
+        assert_eq!(State::new(&input).score, 3.min(2 + BLOCK_PENALTY));
+    }
 }
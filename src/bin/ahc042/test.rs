@@ -9,11 +9,15 @@ mod tests {
     use std::process::{Command, Stdio};
     use std::thread;
 
+    use crate::trajectory;
+
     #[derive(Debug, Serialize, Deserialize)]
     struct Result {
         test_number: String,
         score: usize,
         elapsed_time: f64,
+        #[serde(default)]
+        converged: f64,
         is_ac: bool,
         is_tle: bool,
     }
@@ -27,8 +31,8 @@ mod tests {
             }
             write!(
                 f,
-                "{},{},{},{}",
-                self.test_number, self.score, self.elapsed_time, result
+                "{},{},{},{:.3},{}",
+                self.test_number, self.score, self.elapsed_time, self.converged, result
             )?;
             Ok(())
         }
@@ -87,10 +91,14 @@ mod tests {
             .next()
             .unwrap();
 
+        // スコア推移の出力先
+        let trajectory_file = format!("tools/{}/out/{}_trajectory.csv", exe_filename, test_number);
+
         // run + visualize
         // exp: makers run ahc038 0000
         let run_output = Command::new("makers")
             .args(["run", exe_filename, test_number.as_str()])
+            .env("TRAJECTORY", &trajectory_file)
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .output()
@@ -117,8 +125,19 @@ mod tests {
 
         let delta_score = vis_score as i64 - before_score as i64;
 
+        // スコア推移をプロットし、最良スコアに到達した時刻を取得
+        let records = trajectory::load(&trajectory_file);
+        let converged = trajectory::converged_time(&records);
+        if records.len() > 0 {
+            std::fs::write(
+                trajectory_file.replace(".csv", ".svg"),
+                trajectory::plot(&records, TLE),
+            )
+            .unwrap();
+        }
+
         println!(
-            "{}: score={}, elapsed={}, delta={}",
+            "{}: score={}, elapsed={}, converged={:.3}, delta={}",
             if vis_score == 0 {
                 test_number.to_string().red()
             } else {
@@ -130,6 +149,7 @@ mod tests {
             } else {
                 elapsed_time.to_string().white()
             },
+            converged,
             if delta_score == 0 {
                 delta_score.to_string().white()
             } else if delta_score < 0 {
@@ -143,6 +163,7 @@ mod tests {
             test_number,
             score: vis_score,
             elapsed_time,
+            converged,
             is_ac: vis_score > 0,
             is_tle: elapsed_time > TLE,
         }
@@ -179,7 +200,7 @@ mod tests {
         writeln!(json_file, "{}", serde_json::to_string(&results).unwrap()).unwrap();
 
         let mut file = File::create("results.csv").unwrap();
-        writeln!(file, "{}", "test_num,score,elapsed,result,delta").unwrap();
+        writeln!(file, "{}", "test_num,score,elapsed,converged,result,delta").unwrap();
         let mut score_sum = 0;
         let mut wa_cnt = 0;
         let mut tle_cnt = 0;
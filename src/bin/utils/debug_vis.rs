@@ -0,0 +1,442 @@
+// ソルバー内部の状態をステップごとにレイヤーとして記録し、実行後に egui で閲覧する
+// 記録は feature = "local" のときのみ行われ、提出時は何もしない
+//
+// let mut log = DebugLog::new((0.0, 0.0), (1e4, 1e4));
+// for t in 0..T {
+//     log.step(format!("turn {}", t));
+//     log.points("estimated", &ps, BLUE, 2.0);
+//     log.edges("tree", &es, GRAY, 1.0);
+// }
+// log.show();
+
+pub type Rgb = (u8, u8, u8);
+
+pub const RED: Rgb = (220, 50, 50);
+pub const GREEN: Rgb = (50, 180, 50);
+pub const BLUE: Rgb = (50, 80, 220);
+pub const GRAY: Rgb = (150, 150, 150);
+pub const BLACK: Rgb = (0, 0, 0);
+
+#[derive(Debug, Clone)]
+pub enum Shape {
+    Point {
+        pos: (f64, f64),
+        radius: f32,
+        color: Rgb,
+        alpha: u8,
+    },
+    Edge {
+        from: (f64, f64),
+        to: (f64, f64),
+        width: f32,
+        color: Rgb,
+    },
+    // min ~ max の矩形を values の行数 x 列数で分割して塗る
+    Heatmap {
+        min: (f64, f64),
+        max: (f64, f64),
+        values: Vec<Vec<f64>>,
+    },
+    Text {
+        pos: (f64, f64),
+        text: String,
+        color: Rgb,
+    },
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Layer {
+    pub name: String,
+    pub shapes: Vec<Shape>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Step {
+    pub label: String,
+    pub layers: Vec<Layer>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DebugLog {
+    pub min: (f64, f64),
+    pub max: (f64, f64),
+    pub flip_y: bool,
+    pub steps: Vec<Step>,
+}
+
+impl DebugLog {
+    // min ~ max の範囲のワールド座標を描画領域に合わせる
+    pub fn new(min: (f64, f64), max: (f64, f64)) -> Self {
+        Self {
+            min,
+            max,
+            ..Default::default()
+        }
+    }
+    // y 軸を上向きにする
+    pub fn flip_y(mut self) -> Self {
+        self.flip_y = true;
+        self
+    }
+    #[allow(unused_variables)]
+    pub fn step(&mut self, label: impl Into<String>) {
+        #[cfg(feature = "local")]
+        self.steps.push(Step {
+            label: label.into(),
+            layers: vec![],
+        });
+    }
+    #[allow(unused_variables)]
+    pub fn points(&mut self, layer: &str, ps: &[(f64, f64)], color: Rgb, radius: f32) {
+        #[cfg(feature = "local")]
+        {
+            let shapes = &mut self.layer(layer).shapes;
+            for &pos in ps {
+                shapes.push(Shape::Point {
+                    pos,
+                    radius,
+                    color,
+                    alpha: 255,
+                });
+            }
+        }
+    }
+    // 重み付きの粒子、重みが大きいほど濃く描く
+    #[allow(unused_variables)]
+    pub fn particles(&mut self, layer: &str, ps: &[((f64, f64), f64)], color: Rgb, radius: f32) {
+        #[cfg(feature = "local")]
+        {
+            let wmax = ps.iter().map(|p| p.1).fold(0.0, f64::max);
+            let shapes = &mut self.layer(layer).shapes;
+            for &(pos, w) in ps {
+                let alpha = if wmax > 0.0 { w / wmax } else { 1.0 };
+                shapes.push(Shape::Point {
+                    pos,
+                    radius,
+                    color,
+                    alpha: (40.0 + 215.0 * alpha) as u8,
+                });
+            }
+        }
+    }
+    #[allow(unused_variables)]
+    pub fn edges(&mut self, layer: &str, es: &[((f64, f64), (f64, f64))], color: Rgb, width: f32) {
+        #[cfg(feature = "local")]
+        {
+            let shapes = &mut self.layer(layer).shapes;
+            for &(from, to) in es {
+                shapes.push(Shape::Edge {
+                    from,
+                    to,
+                    width,
+                    color,
+                });
+            }
+        }
+    }
+    #[allow(unused_variables)]
+    pub fn heatmap(
+        &mut self,
+        layer: &str,
+        min: (f64, f64),
+        max: (f64, f64),
+        values: &Vec<Vec<f64>>,
+    ) {
+        #[cfg(feature = "local")]
+        self.layer(layer).shapes.push(Shape::Heatmap {
+            min,
+            max,
+            values: values.clone(),
+        });
+    }
+    #[allow(unused_variables)]
+    pub fn text(&mut self, layer: &str, pos: (f64, f64), text: impl Into<String>, color: Rgb) {
+        #[cfg(feature = "local")]
+        self.layer(layer).shapes.push(Shape::Text {
+            pos,
+            text: text.into(),
+            color,
+        });
+    }
+    // 現在のステップのレイヤー、step() が呼ばれていなければ作る
+    fn layer(&mut self, name: &str) -> &mut Layer {
+        if self.steps.is_empty() {
+            self.steps.push(Step::default());
+        }
+        let step = self.steps.last_mut().unwrap();
+        let idx = match step.layers.iter().position(|l| l.name == name) {
+            Some(idx) => idx,
+            None => {
+                step.layers.push(Layer {
+                    name: name.to_string(),
+                    shapes: vec![],
+                });
+                step.layers.len() - 1
+            }
+        };
+        &mut step.layers[idx]
+    }
+    // 記録した内容をウィンドウで表示する (閉じるまで戻らない)
+    pub fn show(self) {
+        #[cfg(feature = "local")]
+        if !self.steps.is_empty() {
+            viewer::run(self);
+        }
+    }
+}
+
+#[cfg(feature = "local")]
+mod viewer {
+    use super::{DebugLog, Rgb, Shape};
+    use eframe::egui::{
+        show_tooltip_at_pointer, Align2, CentralPanel, Color32, Context, FontFamily, FontId, Id,
+        Key, Pos2, Rect, RichText, SidePanel, Slider, Stroke, Ui,
+    };
+    use eframe::{run_native, App, Frame, NativeOptions, Storage, Theme};
+    use std::time::{Duration, Instant};
+
+    const WIDTH: f32 = 1000.0;
+    const HEIGHT: f32 = 800.0;
+    const VIS_SIZE: f32 = 700.0;
+    const OFFSET_X: f32 = 20.0;
+    const OFFSET_Y: f32 = 90.0;
+    const SPEED_MIN: usize = 1;
+    const SPEED_MAX: usize = 10;
+
+    struct Egui {
+        log: DebugLog,
+        names: Vec<String>,
+        visible: Vec<bool>,
+        step: usize,
+        play: bool,
+        speed: usize,
+        instant: Instant,
+        cnt: usize,
+    }
+
+    impl Egui {
+        fn new(log: DebugLog) -> Self {
+            let mut names: Vec<String> = vec![];
+            for step in log.steps.iter() {
+                for layer in step.layers.iter() {
+                    if !names.contains(&layer.name) {
+                        names.push(layer.name.clone());
+                    }
+                }
+            }
+            let visible = vec![true; names.len()];
+            Egui {
+                log,
+                names,
+                visible,
+                step: 0,
+                play: false,
+                speed: 5,
+                instant: Instant::now(),
+                cnt: 0,
+            }
+        }
+        fn scale(&self) -> f32 {
+            let w = (self.log.max.0 - self.log.min.0).max(1e-9);
+            let h = (self.log.max.1 - self.log.min.1).max(1e-9);
+            VIS_SIZE / w.max(h) as f32
+        }
+        fn to_screen(&self, p: (f64, f64)) -> Pos2 {
+            let d = self.scale();
+            let x = (p.0 - self.log.min.0) as f32 * d;
+            let y = if self.log.flip_y {
+                (self.log.max.1 - p.1) as f32 * d
+            } else {
+                (p.1 - self.log.min.1) as f32 * d
+            };
+            Pos2 {
+                x: x + OFFSET_X,
+                y: y + OFFSET_Y,
+            }
+        }
+        fn to_world(&self, p: Pos2) -> (f64, f64) {
+            let d = self.scale();
+            let x = ((p.x - OFFSET_X) / d) as f64 + self.log.min.0;
+            let y = ((p.y - OFFSET_Y) / d) as f64;
+            let y = if self.log.flip_y {
+                self.log.max.1 - y
+            } else {
+                y + self.log.min.1
+            };
+            (x, y)
+        }
+        fn view(&self, ui: &mut Ui) {
+            let painter = ui.painter();
+            let frame = Rect {
+                min: self.to_screen(self.log.min),
+                max: self.to_screen(self.log.max),
+            };
+            painter.rect_stroke(frame, 0.0, Stroke::new(1.0, Color32::BLACK));
+            let step = &self.log.steps[self.step];
+            for (k, name) in self.names.iter().enumerate() {
+                if !self.visible[k] {
+                    continue;
+                }
+                let Some(layer) = step.layers.iter().find(|l| &l.name == name) else {
+                    continue;
+                };
+                for shape in layer.shapes.iter() {
+                    match shape {
+                        Shape::Point {
+                            pos,
+                            radius,
+                            color,
+                            alpha,
+                        } => {
+                            let (r, g, b) = *color;
+                            painter.circle_filled(
+                                self.to_screen(*pos),
+                                *radius,
+                                Color32::from_rgba_unmultiplied(r, g, b, *alpha),
+                            );
+                        }
+                        Shape::Edge {
+                            from,
+                            to,
+                            width,
+                            color,
+                        } => {
+                            painter.line_segment(
+                                [self.to_screen(*from), self.to_screen(*to)],
+                                Stroke::new(*width, rgb(*color)),
+                            );
+                        }
+                        Shape::Heatmap { min, max, values } => {
+                            let lo = values.iter().flatten().cloned().fold(f64::MAX, f64::min);
+                            let hi = values.iter().flatten().cloned().fold(f64::MIN, f64::max);
+                            let n = values.len();
+                            for (i, row) in values.iter().enumerate() {
+                                let m = row.len();
+                                for (j, &v) in row.iter().enumerate() {
+                                    let p1 = (
+                                        min.0 + (max.0 - min.0) * j as f64 / m as f64,
+                                        min.1 + (max.1 - min.1) * i as f64 / n as f64,
+                                    );
+                                    let p2 = (
+                                        min.0 + (max.0 - min.0) * (j + 1) as f64 / m as f64,
+                                        min.1 + (max.1 - min.1) * (i + 1) as f64 / n as f64,
+                                    );
+                                    let val = if hi > lo { (v - lo) / (hi - lo) } else { 0.5 };
+                                    painter.rect_filled(
+                                        Rect::from_two_pos(self.to_screen(p1), self.to_screen(p2)),
+                                        0.0,
+                                        color32(val as f32).linear_multiply(0.6),
+                                    );
+                                }
+                            }
+                        }
+                        Shape::Text { pos, text, color } => {
+                            painter.text(
+                                self.to_screen(*pos),
+                                Align2::CENTER_CENTER,
+                                text,
+                                FontId::new(12.0, FontFamily::Monospace),
+                                rgb(*color),
+                            );
+                        }
+                    }
+                }
+            }
+            if let Some(hover_pos) = ui.input().pointer.hover_pos() {
+                if frame.contains(hover_pos) {
+                    let (x, y) = self.to_world(hover_pos);
+                    show_tooltip_at_pointer(ui.ctx(), Id::new("hover tooltip"), |ui| {
+                        ui.label(format!("(x, y) = ({:.1}, {:.1})", x, y));
+                    });
+                }
+            }
+        }
+    }
+
+    impl App for Egui {
+        fn save(&mut self, _storage: &mut dyn Storage) {}
+        fn update(&mut self, ctx: &Context, _frame: &mut Frame) {
+            let max_step = self.log.steps.len() - 1;
+            ctx.request_repaint_after(Duration::from_millis(5));
+            if self.instant.elapsed() >= Duration::from_millis(10) {
+                self.cnt += 1;
+                if self.cnt % (SPEED_MIN + SPEED_MAX - self.speed) == 0
+                    && self.play
+                    && self.step < max_step
+                {
+                    self.step += 1;
+                }
+                self.instant = Instant::now();
+            }
+
+            SidePanel::right("layers").show(ctx, |ui| {
+                ui.label(RichText::new("Layers").size(20.0));
+                for (name, visible) in self.names.iter().zip(self.visible.iter_mut()) {
+                    ui.checkbox(visible, name.as_str());
+                }
+            });
+            CentralPanel::default().show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(RichText::new("Step: ").size(20.0));
+                    ui.add(Slider::new(&mut self.step, 0..=max_step));
+                    ui.label(RichText::new(&self.log.steps[self.step].label).size(20.0));
+                });
+                ui.horizontal(|ui| {
+                    ui.label(RichText::new("Speed: ").size(20.0));
+                    ui.add(Slider::new(&mut self.speed, SPEED_MIN..=SPEED_MAX));
+                });
+                self.view(ui);
+
+                if ctx.input().key_released(Key::Space) {
+                    self.play = !self.play;
+                };
+                if self.step == max_step {
+                    self.play = false;
+                }
+                if ctx.input().key_pressed(Key::ArrowRight) && self.step < max_step {
+                    self.step += 1;
+                };
+                if ctx.input().key_pressed(Key::ArrowLeft) && self.step > 0 {
+                    self.step -= 1;
+                };
+            });
+        }
+    }
+
+    pub fn run(log: DebugLog) {
+        let options = NativeOptions {
+            initial_window_size: Some((WIDTH, HEIGHT).into()),
+            initial_window_pos: Some(Pos2 { x: 100.0, y: 100.0 }),
+            default_theme: Theme::Light,
+            ..NativeOptions::default()
+        };
+        let gui = Egui::new(log);
+        run_native("debug visualizer", options, Box::new(|_cc| Box::new(gui)));
+    }
+
+    fn rgb((r, g, b): Rgb) -> Color32 {
+        Color32::from_rgb(r, g, b)
+    }
+
+    // 0 <= val <= 1
+    fn color32(mut val: f32) -> Color32 {
+        val = val.min(1.0);
+        val = val.max(0.0);
+        let (r, g, b) = if val < 0.5 {
+            let x = val * 2.0;
+            (
+                30. * (1.0 - x) + 144. * x,
+                144. * (1.0 - x) + 255. * x,
+                255. * (1.0 - x) + 30. * x,
+            )
+        } else {
+            let x = val * 2.0 - 1.0;
+            (
+                144. * (1.0 - x) + 255. * x,
+                255. * (1.0 - x) + 30. * x,
+                30. * (1.0 - x) + 70. * x,
+            )
+        };
+        Color32::from_rgb(r.round() as u8, g.round() as u8, b.round() as u8)
+    }
+}
@@ -1,6 +1,10 @@
 mod beam;
+// 呼び出し元はコピー先のソルバーにある
+#[allow(dead_code)]
+mod debug_vis;
 mod hash;
 mod input;
 mod state;
+mod trajectory;
 
 fn main() {}
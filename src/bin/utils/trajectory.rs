@@ -0,0 +1,230 @@
+/// 焼きなまし・ビームサーチのスコア推移
+/// feature = "local" のときのみ記録し、環境変数 TRAJECTORY で指定された csv に書き出す
+/// 各ソルバーの main.rs から #[path = "../utils/trajectory.rs"] で読み込む
+#[derive(Debug, Default)]
+pub struct Trajectory {
+    interval: f64,
+    last: f64,
+    tried: usize,
+    accepted: usize,
+    pub records: Vec<Record>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Record {
+    pub time: f64,
+    pub phase: usize,
+    pub best: i64,
+    pub current: i64, // ビームサーチでは層の最悪のスコア
+    pub temp: f64,
+    pub accept_rate: f64, // ビームサーチでは候補のうち層に残った割合
+}
+
+impl Trajectory {
+    pub fn new(interval: f64) -> Self {
+        Self {
+            interval,
+            last: -1.0,
+            ..Default::default()
+        }
+    }
+    /// 1回の遷移判定ごとに呼ぶ
+    /// interval 秒ごとに、その間の受理率とともに記録する
+    #[allow(unused_variables)]
+    pub fn push(
+        &mut self,
+        time: f64,
+        phase: usize,
+        best: i64,
+        current: i64,
+        temp: f64,
+        accepted: bool,
+    ) {
+        #[cfg(feature = "local")]
+        {
+            self.tried += 1;
+            if accepted {
+                self.accepted += 1;
+            }
+            if time - self.last >= self.interval {
+                self.records.push(Record {
+                    time,
+                    phase,
+                    best,
+                    current,
+                    temp,
+                    accept_rate: self.accepted as f64 / self.tried as f64,
+                });
+                self.last = time;
+                self.tried = 0;
+                self.accepted = 0;
+            }
+        }
+    }
+    /// ビームサーチの1層ごとに呼ぶ
+    /// 層の最良・最悪のスコアと、候補のうち層に残った割合を毎回記録する
+    /// phase には層の番号 (ターン) を渡す
+    #[allow(unused_variables)]
+    pub fn push_layer(
+        &mut self,
+        time: f64,
+        phase: usize,
+        best: i64,
+        worst: i64,
+        kept: usize,
+        cand_num: usize,
+    ) {
+        #[cfg(feature = "local")]
+        {
+            self.records.push(Record {
+                time,
+                phase,
+                best,
+                current: worst,
+                temp: 0.0,
+                accept_rate: kept as f64 / cand_num.max(1) as f64,
+            });
+            self.last = time;
+        }
+    }
+    pub fn save(&self) {
+        #[cfg(feature = "local")]
+        {
+            use std::io::Write;
+            let Ok(path) = std::env::var("TRAJECTORY") else {
+                return;
+            };
+            let mut w = std::io::BufWriter::new(std::fs::File::create(path).unwrap());
+            writeln!(w, "time,phase,best,current,temp,accept_rate").unwrap();
+            for r in self.records.iter() {
+                writeln!(
+                    w,
+                    "{:.4},{},{},{},{},{:.4}",
+                    r.time, r.phase, r.best, r.current, r.temp, r.accept_rate
+                )
+                .unwrap();
+            }
+        }
+    }
+}
+
+pub fn load(path: &str) -> Vec<Record> {
+    let Ok(s) = std::fs::read_to_string(path) else {
+        return vec![];
+    };
+    s.lines()
+        .skip(1)
+        .filter_map(|line| {
+            let v = line.split(',').collect::<Vec<_>>();
+            if v.len() != 6 {
+                return None;
+            }
+            Some(Record {
+                time: v[0].parse().ok()?,
+                phase: v[1].parse().ok()?,
+                best: v[2].parse().ok()?,
+                current: v[3].parse().ok()?,
+                temp: v[4].parse().ok()?,
+                accept_rate: v[5].parse().ok()?,
+            })
+        })
+        .collect()
+}
+
+/// 最良スコアが最終値に初めて到達した時刻
+pub fn converged_time(records: &[Record]) -> f64 {
+    let Some(last) = records.last() else {
+        return 0.0;
+    };
+    records
+        .iter()
+        .find(|r| r.best == last.best)
+        .map_or(0.0, |r| r.time)
+}
+
+/// best (赤), current (青), 受理率 (緑, 0~1) の折れ線グラフ
+/// フェーズの切り替わりに縦線を引く
+pub fn plot(records: &[Record], tle: f64) -> String {
+    let W = 800.0;
+    let H = 400.0;
+    let lo = records
+        .iter()
+        .map(|r| r.best.min(r.current))
+        .min()
+        .unwrap_or(0);
+    let hi = records
+        .iter()
+        .map(|r| r.best.max(r.current))
+        .max()
+        .unwrap_or(1)
+        .max(lo + 1);
+    let tmax = records.last().map_or(tle, |r| r.time.max(tle));
+    let x = |t: f64| t / tmax * W;
+    let y = |s: i64| H - (s - lo) as f64 / (hi - lo) as f64 * H;
+    let polyline = |ps: Vec<(f64, f64)>, color: &str| {
+        format!(
+            "<polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"1\"/>",
+            ps.iter()
+                .map(|(x, y)| format!("{:.1},{:.1}", x, y))
+                .collect::<Vec<_>>()
+                .join(" "),
+            color
+        )
+    };
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"-60 -10 {} {}\" width=\"{}\" height=\"{}\" style=\"background-color:white\">",
+        W + 70.0,
+        H + 40.0,
+        W + 70.0,
+        H + 40.0
+    );
+    svg += &format!(
+        "<rect x=\"0\" y=\"0\" width=\"{}\" height=\"{}\" fill=\"none\" stroke=\"gray\"/>",
+        W, H
+    );
+    svg += &format!(
+        "<line x1=\"{0}\" y1=\"0\" x2=\"{0}\" y2=\"{1}\" stroke=\"black\" stroke-dasharray=\"4\"/>",
+        x(tle),
+        H
+    );
+    for w in records.windows(2) {
+        if w[0].phase != w[1].phase {
+            svg += &format!(
+                "<line x1=\"{0}\" y1=\"0\" x2=\"{0}\" y2=\"{1}\" stroke=\"lightgray\"/>",
+                x(w[1].time),
+                H
+            );
+        }
+    }
+    svg += &polyline(
+        records
+            .iter()
+            .map(|r| (x(r.time), H - r.accept_rate * H))
+            .collect(),
+        "green",
+    );
+    svg += &polyline(
+        records.iter().map(|r| (x(r.time), y(r.current))).collect(),
+        "blue",
+    );
+    svg += &polyline(
+        records.iter().map(|r| (x(r.time), y(r.best))).collect(),
+        "red",
+    );
+    svg += &format!(
+        "<text x=\"-5\" y=\"5\" text-anchor=\"end\" font-size=\"12\">{}</text>",
+        hi
+    );
+    svg += &format!(
+        "<text x=\"-5\" y=\"{}\" text-anchor=\"end\" font-size=\"12\">{}</text>",
+        H, lo
+    );
+    svg += &format!(
+        "<text x=\"{}\" y=\"{}\" text-anchor=\"end\" font-size=\"12\">{:.2}s</text>",
+        W,
+        H + 20.0,
+        tmax
+    );
+    svg += "</svg>";
+    svg
+}
@@ -0,0 +1,20 @@
+#![allow(non_snake_case)]
+
+use clap::Parser;
+use tools::*;
+
+#[path = "../../../utils/anim.rs"]
+mod anim;
+
+fn main() {
+    let cli = anim::Cli::parse();
+    let (input, output) = cli.read_files();
+    let input = parse_input(&input);
+    let out = parse_output(&input, &output).unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        std::process::exit(1)
+    });
+    anim::write_gif(&cli, out.S.len(), |t| {
+        vis(&input, &out, t as f64, false, 1).vis
+    });
+}
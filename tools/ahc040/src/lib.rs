@@ -172,7 +172,7 @@ pub fn gen(seed: u64, fix_N: Option<usize>, fix_T: Option<usize>, fix_sigma: Opt
     }
     let mut T = (N as f64 * f64::powf(2.0, rng.gen_range(-1.0..2.0))).round() as usize;
     if let Some(v) = fix_T {
-        T = v;
+        T = v.clamp((N + 1) / 2, 4 * N);
     }
     let ub = 100000;
     let mut sigma = rng.gen_range(ub / 100..=ub / 10);
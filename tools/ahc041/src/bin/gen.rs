@@ -14,6 +14,12 @@ struct Cli {
     #[clap(short, long)]
     /// Print input details in csv format
     verbose: bool,
+    /// Fix N to the specified value (e.g. small instances for the exact solver)
+    #[clap(long = "N")]
+    N: Option<usize>,
+    /// Fix H to the specified value
+    #[clap(long = "H")]
+    H: Option<i64>,
 }
 
 fn main() {
@@ -40,7 +46,7 @@ fn main() {
             eprintln!("parse failed: {}", line);
             std::process::exit(1)
         });
-        let input = gen(seed);
+        let input = gen(seed, cli.N, cli.H);
         if cli.verbose {
             println!("{:04},{}", id, seed);
         }
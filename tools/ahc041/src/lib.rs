@@ -246,9 +246,9 @@ pub fn parse_output(input: &Input, f: &str) -> Result<Output> {
     Ok(Output::new(out))
 }
 
-pub fn gen(seed: u64) -> Input {
-    const N: usize = 1000;
-    const H: i64 = 10;
+pub fn gen(seed: u64, fix_N: Option<usize>, fix_H: Option<i64>) -> Input {
+    let N: usize = fix_N.unwrap_or(1000);
+    let H: i64 = fix_H.unwrap_or(10);
     let mut rng = rand_chacha::ChaCha20Rng::seed_from_u64(seed);
 
     // gen points
@@ -5,6 +5,11 @@ use rand::prelude::*;
 use std::ops::RangeBounds;
 use svg::node::element::{Definitions, Group, Image, Rectangle, Style, Text, Title, Use};
 
+#[path = "../../utils/compare.rs"]
+mod compare;
+
+pub use compare::compare_html;
+
 pub trait SetMinMax {
     fn setmin(&mut self, v: Self) -> bool;
     fn setmax(&mut self, v: Self) -> bool;
@@ -150,44 +155,57 @@ pub fn compute_score_details(
     input: &Input,
     out: &[(char, usize)],
 ) -> (i64, String, Vec<Vec<char>>) {
-    let n = input.cs.len();
     let mut cs = input.cs.clone();
     for &(d, p) in out {
-        match d {
-            'L' => {
-                let i = p;
-                for j in 0..n - 1 {
-                    cs[i][j] = cs[i][j + 1];
-                }
-                cs[i][n - 1] = '.';
-            }
-            'R' => {
-                let i = p;
-                for j in (1..n).rev() {
-                    cs[i][j] = cs[i][j - 1];
-                }
-                cs[i][0] = '.';
+        if let Err(err) = apply(&mut cs, d, p) {
+            return (0, err, cs);
+        }
+    }
+    (board_score(&cs, out.len()), String::new(), cs)
+}
+
+/// Shifts the row or column p of the board in the direction d.
+pub fn apply(cs: &mut Vec<Vec<char>>, d: char, p: usize) -> Result<(), String> {
+    let n = cs.len();
+    match d {
+        'L' => {
+            let i = p;
+            for j in 0..n - 1 {
+                cs[i][j] = cs[i][j + 1];
             }
-            'U' => {
-                let j = p;
-                for i in 0..n - 1 {
-                    cs[i][j] = cs[i + 1][j];
-                }
-                cs[n - 1][j] = '.';
+            cs[i][n - 1] = '.';
+        }
+        'R' => {
+            let i = p;
+            for j in (1..n).rev() {
+                cs[i][j] = cs[i][j - 1];
             }
-            'D' => {
-                let j = p;
-                for i in (1..n).rev() {
-                    cs[i][j] = cs[i - 1][j];
-                }
-                cs[0][j] = '.';
+            cs[i][0] = '.';
+        }
+        'U' => {
+            let j = p;
+            for i in 0..n - 1 {
+                cs[i][j] = cs[i + 1][j];
             }
-            _ => {
-                return (0, format!("Invalid direction: {}", d), cs);
+            cs[n - 1][j] = '.';
+        }
+        'D' => {
+            let j = p;
+            for i in (1..n).rev() {
+                cs[i][j] = cs[i - 1][j];
             }
+            cs[0][j] = '.';
+        }
+        _ => {
+            return Err(format!("Invalid direction: {}", d));
         }
     }
-    let T = out.len();
+    Ok(())
+}
+
+/// Score of the board after T operations.
+pub fn board_score(cs: &Vec<Vec<char>>, T: usize) -> i64 {
+    let n = cs.len();
     let mut X = 0;
     let mut Y = 2 * n;
     for i in 0..n {
@@ -205,7 +223,7 @@ pub fn compute_score_details(
     } else {
         4 * n * n - n * (X + Y)
     };
-    (score as i64, String::new(), cs)
+    score as i64
 }
 
 /// 0 <= val <= 1
@@ -392,3 +410,28 @@ pub fn vis(input: &Input, out: &[(char, usize)], manual: bool) -> (i64, String,
     }
     (score, err, doc.to_string())
 }
+
+/// Per-turn (score, rows) of the board, used by the compare mode.
+/// After an invalid operation, the board stays as it is and the score is 0.
+pub fn compare_frames(input: &Input, out: &[(char, usize)]) -> Vec<(i64, Vec<String>)> {
+    let rows = |cs: &Vec<Vec<char>>| cs.iter().map(|cs| cs.iter().collect()).collect();
+    let mut cs = input.cs.clone();
+    let mut ok = true;
+    let mut frames = vec![(board_score(&cs, 0), rows(&cs))];
+    for (t, &(d, p)) in out.iter().enumerate() {
+        ok = ok && apply(&mut cs, d, p).is_ok();
+        let score = if ok { board_score(&cs, t + 1) } else { 0 };
+        frames.push((score, rows(&cs)));
+    }
+    frames
+}
+
+pub fn vis_compare(input: &Input, out_a: &Output, out_b: &Output) -> String {
+    let frames_a = compare_frames(input, &out_a.out);
+    let frames_b = compare_frames(input, &out_b.out);
+    compare_html(
+        &frames_a,
+        &frames_b,
+        &[('.', "#f0f0f0"), ('x', "#e04040"), ('o', "#40a0e0")],
+    )
+}
@@ -4,6 +4,18 @@ use clap::Parser;
 use std::{io::prelude::*, path::PathBuf};
 use tools::*;
 
+#[path = "../../../utils/sweep.rs"]
+mod sweep;
+
+use sweep::Param;
+
+// 生成器が取りうる値の範囲
+const PARAMS: [Param; 3] = [
+    Param::new("M", 1, 400),
+    Param::new("L", 3, 15),
+    Param::new("W", 500, 2500),
+];
+
 #[derive(Parser, Debug)]
 struct Cli {
     /// Path to seeds.txt
@@ -23,6 +35,15 @@ struct Cli {
     /// Fix W to the specified value
     #[clap(long = "W")]
     W: Option<usize>,
+    /// Sweep a parameter over a range, e.g. `--sweep M=1:400:5` (name=lo:hi:points)
+    #[clap(long = "sweep")]
+    sweep: Vec<String>,
+    /// Sample the sweep ranges by latin hypercube (`points` samples, the same for every parameter) instead of a grid
+    #[clap(long = "stratified")]
+    stratified: bool,
+    /// Number of seeds used for each sweep point
+    #[clap(long = "repeat", default_value_t = 1)]
+    repeat: usize,
 }
 
 fn main() {
@@ -35,10 +56,7 @@ fn main() {
         std::process::exit(1)
     });
     let f = std::io::BufReader::new(f);
-    let mut id = 0;
-    if cli.verbose {
-        println!("file,seed,N,M,Q,L,W");
-    }
+    let mut seeds = vec![];
     for line in f.lines() {
         let line = line.unwrap();
         let line = line.trim();
@@ -49,12 +67,55 @@ fn main() {
             eprintln!("parse failed: {}", line);
             std::process::exit(1)
         });
-        let input = gen(seed, cli.M, cli.L, cli.W);
+        seeds.push(seed);
+    }
+    let jobs = sweep::jobs(
+        &seeds,
+        &PARAMS,
+        vec![
+            cli.M.map(|v| v as i64),
+            cli.L.map(|v| v as i64),
+            cli.W.map(|v| v as i64),
+        ],
+        &cli.sweep,
+        cli.stratified,
+        cli.repeat,
+    );
+    let mut manifest = if cli.sweep.len() > 0 {
+        let mut w =
+            std::io::BufWriter::new(std::fs::File::create(cli.dir.join("manifest.csv")).unwrap());
+        writeln!(w, "file,seed,N,M,Q,L,W").unwrap();
+        Some(w)
+    } else {
+        None
+    };
+    if cli.verbose {
+        println!("file,seed,N,M,Q,L,W");
+    }
+    for (id, (seed, fix)) in jobs.into_iter().enumerate() {
+        let input = gen(
+            seed,
+            fix[0].map(|v| v as usize),
+            fix[1].map(|v| v as usize),
+            fix[2].map(|v| v as usize),
+        );
         if cli.verbose {
-            println!("{:04},{},{},{},{},{},{}", id, seed, input.N, input.M, input.Q, input.L, input.W);
+            println!(
+                "{:04},{},{},{},{},{},{}",
+                id, seed, input.N, input.M, input.Q, input.L, input.W
+            );
+        }
+        if let Some(w) = manifest.as_mut() {
+            writeln!(
+                w,
+                "{:04},{},{},{},{},{},{}",
+                id, seed, input.N, input.M, input.Q, input.L, input.W
+            )
+            .unwrap();
         }
-        let mut w = std::io::BufWriter::new(std::fs::File::create(cli.dir.join(format!("{:04}.txt", id))).unwrap());
+        let mut w = std::io::BufWriter::new(
+            std::fs::File::create(cli.dir.join(format!("{:04}.txt", id))).unwrap(),
+        );
         write!(w, "{}", input).unwrap();
-        id += 1;
     }
 }
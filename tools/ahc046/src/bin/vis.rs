@@ -3,8 +3,16 @@
 use tools::*;
 
 fn main() {
+    if std::env::args().len() == 5 && std::env::args().nth(2).unwrap() == "--compare" {
+        compare();
+        return;
+    }
     if std::env::args().len() != 3 {
         eprintln!("Usage: {} <input> <output>", std::env::args().nth(0).unwrap());
+        eprintln!(
+            "       {} <input> --compare <output_a> <output_b>",
+            std::env::args().nth(0).unwrap()
+        );
         return;
     }
     let in_file = std::env::args().nth(1).unwrap();
@@ -32,3 +40,32 @@ fn main() {
     let vis = format!("<html><body>{}</body></html>", svg);
     std::fs::write("vis.html", &vis).unwrap();
 }
+
+fn compare() {
+    let in_file = std::env::args().nth(1).unwrap();
+    let input = std::fs::read_to_string(&in_file).unwrap_or_else(|_| {
+        eprintln!("no such file: {}", in_file);
+        std::process::exit(1)
+    });
+    let input = parse_input(&input);
+    let mut outs = vec![];
+    for k in 3..5 {
+        let out_file = std::env::args().nth(k).unwrap();
+        let output = std::fs::read_to_string(&out_file).unwrap_or_else(|_| {
+            eprintln!("no such file: {}", out_file);
+            std::process::exit(1)
+        });
+        let out = parse_output(&input, &output).unwrap_or_else(|err| {
+            eprintln!("{}: {}", out_file, err);
+            std::process::exit(1)
+        });
+        let (score, err) = compute_score(&input, &out);
+        if err.len() > 0 {
+            println!("{}", err);
+        }
+        println!("{}: Score = {}", out_file, score);
+        outs.push(out);
+    }
+    let vis = vis_compare(&input, &outs[0], &outs[1]);
+    std::fs::write("vis.html", &vis).unwrap();
+}
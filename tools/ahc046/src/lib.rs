@@ -5,6 +5,11 @@ use rand::prelude::*;
 use std::ops::RangeBounds;
 use svg::node::element::{Group, Line, Rectangle, Style, Symbol, Title, Use};
 
+#[path = "../../utils/compare.rs"]
+mod compare;
+
+pub use compare::compare_html;
+
 pub trait SetMinMax {
     fn setmin(&mut self, v: Self) -> bool;
     fn setmax(&mut self, v: Self) -> bool;
@@ -150,61 +155,77 @@ pub struct State {
 }
 
 pub fn compute_score_details(input: &Input, out: &[Action]) -> (i64, String, State) {
-    let mut state = State {
-        pi: input.ps[0].0,
-        pj: input.ps[0].1,
-        done: 1,
-        block: mat![false; input.N; input.N],
-        prev: input.ps[0],
-    };
+    let mut state = State::new(input);
     for action in out {
-        state.prev = (state.pi, state.pj);
+        if let Err(err) = state.apply(input, action) {
+            return (0, err, state);
+        }
+    }
+    (state.score(input, out.len()), String::new(), state)
+}
+
+impl State {
+    pub fn new(input: &Input) -> Self {
+        State {
+            pi: input.ps[0].0,
+            pj: input.ps[0].1,
+            done: 1,
+            block: mat![false; input.N; input.N],
+            prev: input.ps[0],
+        }
+    }
+    /// Applies one action.
+    pub fn apply(&mut self, input: &Input, action: &Action) -> Result<(), String> {
+        self.prev = (self.pi, self.pj);
         match *action {
             Action::Move(dir) => {
                 let (di, dj) = DIJ[dir];
-                let pi = state.pi + di;
-                let pj = state.pj + dj;
+                let pi = self.pi + di;
+                let pj = self.pj + dj;
                 if pi >= input.N || pj >= input.N {
-                    return (0, format!("Out of range (M {})", DIR[dir]), state);
+                    return Err(format!("Out of range (M {})", DIR[dir]));
                 }
-                if state.block[pi][pj] {
-                    return (0, format!("Blocked (M {})", DIR[dir]), state);
+                if self.block[pi][pj] {
+                    return Err(format!("Blocked (M {})", DIR[dir]));
                 }
-                state.pi = pi;
-                state.pj = pj;
+                self.pi = pi;
+                self.pj = pj;
             }
             Action::Slide(dir) => {
                 let (di, dj) = DIJ[dir];
                 loop {
-                    let pi = state.pi + di;
-                    let pj = state.pj + dj;
-                    if pi >= input.N || pj >= input.N || state.block[pi][pj] {
+                    let pi = self.pi + di;
+                    let pj = self.pj + dj;
+                    if pi >= input.N || pj >= input.N || self.block[pi][pj] {
                         break;
                     }
-                    state.pi = pi;
-                    state.pj = pj;
+                    self.pi = pi;
+                    self.pj = pj;
                 }
             }
             Action::Alter(dir) => {
                 let (di, dj) = DIJ[dir];
-                let i = state.pi + di;
-                let j = state.pj + dj;
+                let i = self.pi + di;
+                let j = self.pj + dj;
                 if i >= input.N || j >= input.N {
-                    return (0, format!("Out of range (A {})", DIR[dir]), state);
+                    return Err(format!("Out of range (A {})", DIR[dir]));
                 }
-                state.block[i][j] ^= true;
+                self.block[i][j] ^= true;
             }
         }
-        if state.done < input.ps.len() && input.ps[state.done] == (state.pi, state.pj) {
-            state.done += 1;
+        if self.done < input.ps.len() && input.ps[self.done] == (self.pi, self.pj) {
+            self.done += 1;
+        }
+        Ok(())
+    }
+    /// Score after T actions.
+    pub fn score(&self, input: &Input, T: usize) -> i64 {
+        if self.done < input.ps.len() {
+            self.done as i64
+        } else {
+            input.ps.len() as i64 + (2 * input.N * input.ps.len()) as i64 - T as i64
         }
     }
-    let score = if state.done < input.ps.len() {
-        state.done as i64
-    } else {
-        input.ps.len() as i64 + (2 * input.N * input.ps.len()) as i64 - out.len() as i64
-    };
-    (score, String::new(), state)
 }
 
 /// 0 <= val <= 1
@@ -358,3 +379,49 @@ pub fn vis(input: &Input, out: &[Action]) -> (i64, String, String) {
     }
     (score, err, doc.to_string())
 }
+
+/// Per-turn (score, rows) of the board, used by the compare mode.
+/// '#' is a block, '@' is the skater and '*' is the next target.
+/// After an invalid action, the board stays as it is and the score is 0.
+pub fn compare_frames(input: &Input, out: &[Action]) -> Vec<(i64, Vec<String>)> {
+    let rows = |state: &State| {
+        let mut rows = mat!['.'; input.N; input.N];
+        for i in 0..input.N {
+            for j in 0..input.N {
+                if state.block[i][j] {
+                    rows[i][j] = '#';
+                }
+            }
+        }
+        if state.done < input.ps.len() {
+            let (i, j) = input.ps[state.done];
+            rows[i][j] = '*';
+        }
+        rows[state.pi][state.pj] = '@';
+        rows.iter().map(|r| r.iter().collect()).collect()
+    };
+    let mut state = State::new(input);
+    let mut ok = true;
+    let mut frames = vec![(state.score(input, 0), rows(&state))];
+    for (t, action) in out.iter().enumerate() {
+        ok = ok && state.apply(input, action).is_ok();
+        let score = if ok { state.score(input, t + 1) } else { 0 };
+        frames.push((score, rows(&state)));
+    }
+    frames
+}
+
+pub fn vis_compare(input: &Input, out_a: &Output, out_b: &Output) -> String {
+    let frames_a = compare_frames(input, &out_a.out);
+    let frames_b = compare_frames(input, &out_b.out);
+    compare_html(
+        &frames_a,
+        &frames_b,
+        &[
+            ('.', "white"),
+            ('#', "gray"),
+            ('*', "#ffb0b0"),
+            ('@', "#8080ff"),
+        ],
+    )
+}
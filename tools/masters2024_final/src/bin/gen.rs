@@ -4,6 +4,13 @@ use clap::Parser;
 use std::{io::prelude::*, path::PathBuf};
 use tools::*;
 
+#[path = "../../../utils/sweep.rs"]
+mod sweep;
+
+use sweep::Param;
+
+const PARAMS: [Param; 1] = [Param::choice("problem", &["A", "B", "C"])];
+
 #[derive(Parser, Debug)]
 struct Cli {
     /// Path to seeds.txt
@@ -15,8 +22,17 @@ struct Cli {
     /// Print input details in csv format
     verbose: bool,
     #[clap(short, long)]
-    /// Problem ('A', 'B', or 'C)
-    problem: char,
+    /// Problem ('A', 'B', or 'C'), can be omitted when sweeping problem
+    problem: Option<char>,
+    /// Sweep the problem id, e.g. `--sweep problem=A:C:3` (name=lo:hi:points)
+    #[clap(long = "sweep")]
+    sweep: Vec<String>,
+    /// Sample the sweep ranges by latin hypercube (`points` samples, the same for every parameter) instead of a grid
+    #[clap(long = "stratified")]
+    stratified: bool,
+    /// Number of seeds used for each sweep point
+    #[clap(long = "repeat", default_value_t = 1)]
+    repeat: usize,
 }
 
 fn main() {
@@ -29,10 +45,7 @@ fn main() {
         std::process::exit(1)
     });
     let f = std::io::BufReader::new(f);
-    let mut id = 0;
-    if cli.verbose {
-        println!("file,seed");
-    }
+    let mut seeds = vec![];
     for line in f.lines() {
         let line = line.unwrap();
         let line = line.trim();
@@ -43,12 +56,43 @@ fn main() {
             eprintln!("parse failed: {}", line);
             std::process::exit(1)
         });
-        let input = gen(seed, cli.problem);
+        seeds.push(seed);
+    }
+    let jobs = sweep::jobs(
+        &seeds,
+        &PARAMS,
+        vec![cli.problem.map(|p| PARAMS[0].parse_fixed(&p.to_string()))],
+        &cli.sweep,
+        cli.stratified,
+        cli.repeat,
+    );
+    let mut manifest = if cli.sweep.len() > 0 {
+        let mut w =
+            std::io::BufWriter::new(std::fs::File::create(cli.dir.join("manifest.csv")).unwrap());
+        writeln!(w, "file,seed,problem").unwrap();
+        Some(w)
+    } else {
+        None
+    };
+    if cli.verbose {
+        println!("file,seed");
+    }
+    for (id, (seed, fix)) in jobs.into_iter().enumerate() {
+        let Some(problem) = fix[0] else {
+            eprintln!("problem is not specified");
+            std::process::exit(1)
+        };
+        let problem = PARAMS[0].format(problem);
+        let input = gen(seed, problem.chars().next().unwrap());
         if cli.verbose {
             println!("{:04},{}", id, seed);
         }
-        let mut w = std::io::BufWriter::new(std::fs::File::create(cli.dir.join(format!("{:04}.txt", id))).unwrap());
+        if let Some(w) = manifest.as_mut() {
+            writeln!(w, "{:04},{},{}", id, seed, problem).unwrap();
+        }
+        let mut w = std::io::BufWriter::new(
+            std::fs::File::create(cli.dir.join(format!("{:04}.txt", id))).unwrap(),
+        );
         write!(w, "{}", input).unwrap();
-        id += 1;
     }
 }
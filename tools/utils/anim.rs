@@ -0,0 +1,80 @@
+// anim の共通処理
+// 各 tools/<contest>/src/bin/anim.rs から #[path = "../../../utils/anim.rs"] で読み込む
+
+use clap::Parser;
+use std::path::PathBuf;
+
+#[derive(Parser, Debug)]
+pub struct Cli {
+    /// Path to input file
+    pub input: String,
+    /// Path to output file
+    pub output: String,
+    /// Path to the generated animation
+    #[clap(short = 'o', long = "out", default_value = "anim.gif")]
+    pub out: PathBuf,
+    /// Number of turns between two frames
+    #[clap(short = 's', long = "stride", default_value_t = 1)]
+    pub stride: usize,
+    /// Width and height of each frame in pixels
+    #[clap(long = "size", default_value_t = 400)]
+    pub size: u32,
+    /// Frames per second
+    #[clap(long = "fps", default_value_t = 10)]
+    pub fps: u32,
+}
+
+impl Cli {
+    /// Reads the input and output files given on the command line.
+    pub fn read_files(&self) -> (String, String) {
+        let read = |path: &String| {
+            std::fs::read_to_string(path).unwrap_or_else(|_| {
+                eprintln!("no such file: {}", path);
+                std::process::exit(1)
+            })
+        };
+        (read(&self.input), read(&self.output))
+    }
+}
+
+/// Writes a gif with one frame every `stride` turns of 0..=T, where `svg_at(t)` draws turn t.
+pub fn write_gif(cli: &Cli, T: usize, svg_at: impl Fn(usize) -> String) {
+    let mut ts = (0..T).step_by(cli.stride.max(1)).collect::<Vec<_>>();
+    ts.push(T);
+
+    let f = std::fs::File::create(&cli.out).unwrap();
+    let mut encoder = gif::Encoder::new(
+        std::io::BufWriter::new(f),
+        cli.size as u16,
+        cli.size as u16,
+        &[],
+    )
+    .unwrap();
+    encoder.set_repeat(gif::Repeat::Infinite).unwrap();
+    let delay = (100 / cli.fps.max(1)) as u16;
+    for (k, &t) in ts.iter().enumerate() {
+        let mut rgba = rasterize(&svg_at(t), cli.size);
+        let mut frame =
+            gif::Frame::from_rgba_speed(cli.size as u16, cli.size as u16, &mut rgba, 10);
+        // 最終フレームは少し長めに表示する
+        frame.delay = if k + 1 == ts.len() { delay * 10 } else { delay };
+        encoder.write_frame(&frame).unwrap();
+    }
+    eprintln!("{} frames -> {}", ts.len(), cli.out.display());
+}
+
+fn rasterize(svg: &str, size: u32) -> Vec<u8> {
+    let mut opt = resvg::usvg::Options::default();
+    // 盤面の数字などの text 要素を描くためにフォントを読み込む
+    opt.fontdb_mut().load_system_fonts();
+    let tree = resvg::usvg::Tree::from_str(svg, &opt).unwrap();
+    let scale = size as f32 / tree.size().width().max(tree.size().height());
+    let mut pixmap = resvg::tiny_skia::Pixmap::new(size, size).unwrap();
+    pixmap.fill(resvg::tiny_skia::Color::WHITE);
+    resvg::render(
+        &tree,
+        resvg::tiny_skia::Transform::from_scale(scale, scale),
+        &mut pixmap.as_mut(),
+    );
+    pixmap.take()
+}
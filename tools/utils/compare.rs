@@ -0,0 +1,96 @@
+// vis --compare の共通処理
+// 各 tools/<contest>/src/lib.rs から #[path = "../../utils/compare.rs"] で読み込む
+
+use svg::node::element::{Line, Polyline, Rectangle};
+
+/// Builds a self-contained html which shows two solutions side by side with a shared turn slider.
+/// Each frame is (score, rows) and cells whose characters differ between the two are highlighted.
+pub fn compare_html(
+    frames_a: &[(i64, Vec<String>)],
+    frames_b: &[(i64, Vec<String>)],
+    colors: &[(char, &str)],
+) -> String {
+    let T = frames_a.len().max(frames_b.len()) - 1;
+    // 短い方は最後の盤面のまま
+    fn at(frames: &[(i64, Vec<String>)], t: usize) -> &(i64, Vec<String>) {
+        &frames[t.min(frames.len() - 1)]
+    }
+    let rows = |rows: &Vec<String>| {
+        format!(
+            "[{}]",
+            rows.iter()
+                .map(|r| format!("\"{}\"", r))
+                .collect::<Vec<_>>()
+                .join(",")
+        )
+    };
+    let mut frames = vec![];
+    for t in 0..=T {
+        let (sa, ra) = at(frames_a, t);
+        let (sb, rb) = at(frames_b, t);
+        frames.push(format!("[{},{},{},{}]", sa, rows(ra), sb, rows(rb)));
+    }
+    let colors = format!(
+        "{{{}}}",
+        colors
+            .iter()
+            .map(|(c, col)| format!("\"{}\":\"{}\"", c, col))
+            .collect::<Vec<_>>()
+            .join(",")
+    );
+
+    // score curve
+    let W = 1210.0;
+    let H = 200.0;
+    let scores = frames_a.iter().chain(frames_b.iter()).map(|f| f.0);
+    let lo = scores.clone().min().unwrap();
+    let hi = scores.max().unwrap().max(lo + 1);
+    let polyline = |frames: &[(i64, Vec<String>)], stroke: &str| {
+        let points = (0..=T)
+            .map(|t| {
+                let s = at(frames, t).0;
+                let x = if T == 0 { 0.0 } else { t as f64 / T as f64 * W };
+                let y = H - (s - lo) as f64 / (hi - lo) as f64 * H;
+                format!("{:.1},{:.1}", x, y)
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+        Polyline::new()
+            .set("points", points)
+            .set("fill", "none")
+            .set("stroke", stroke)
+            .set("stroke-width", 2)
+    };
+    let curve = svg::Document::new()
+        .set("viewBox", (-5, -5, W + 10.0, H + 10.0))
+        .set("width", W + 10.0)
+        .set("height", H + 10.0)
+        .set("style", "background-color:white")
+        .add(
+            Rectangle::new()
+                .set("x", 0)
+                .set("y", 0)
+                .set("width", W)
+                .set("height", H)
+                .set("fill", "none")
+                .set("stroke", "gray"),
+        )
+        .add(polyline(frames_a, "#1f77b4"))
+        .add(polyline(frames_b, "#ff7f0e"))
+        .add(
+            Line::new()
+                .set("id", "marker")
+                .set("x1", 0)
+                .set("x2", 0)
+                .set("y1", 0)
+                .set("y2", H)
+                .set("stroke", "black")
+                .set("stroke-width", 1),
+        );
+    include_str!("compare.html")
+        .replace("__MAX_TURN__", &T.to_string())
+        .replace("__CURVE_WIDTH__", &W.to_string())
+        .replace("__CURVE__", &curve.to_string())
+        .replace("__FRAMES__", &format!("[{}]", frames.join(",\n")))
+        .replace("__COLORS__", &colors)
+}
@@ -0,0 +1,215 @@
+// gen の --sweep / --stratified / --repeat の共通処理
+// 各 tools/<contest>/src/bin/gen.rs から #[path = "../../../utils/sweep.rs"] で読み込む
+
+#![allow(dead_code)]
+
+use rand::prelude::*;
+
+/// 固定・sweep できるパラメータと、生成器が取りうる値の範囲
+/// choices が空でなければ、値は choices の添字で、名前でも指定できる
+pub struct Param {
+    pub name: &'static str,
+    pub lo: i64,
+    pub hi: i64,
+    pub choices: &'static [&'static str],
+}
+
+impl Param {
+    pub const fn new(name: &'static str, lo: i64, hi: i64) -> Self {
+        Self {
+            name,
+            lo,
+            hi,
+            choices: &[],
+        }
+    }
+    pub const fn choice(name: &'static str, choices: &'static [&'static str]) -> Self {
+        Self {
+            name,
+            lo: 0,
+            hi: choices.len() as i64 - 1,
+            choices,
+        }
+    }
+    fn parse(&self, s: &str) -> Option<i64> {
+        if let Some(i) = self.choices.iter().position(|&c| c == s) {
+            return Some(i as i64);
+        }
+        s.parse::<i64>().ok()
+    }
+    /// コマンドライン引数で固定された値
+    pub fn parse_fixed(&self, s: &str) -> i64 {
+        self.parse(s)
+            .unwrap_or_else(|| exit(format!("invalid {}: {}", self.name, s)))
+    }
+    /// manifest に書く値
+    pub fn format(&self, v: i64) -> String {
+        if self.choices.is_empty() {
+            v.to_string()
+        } else {
+            self.choices[v as usize].to_string()
+        }
+    }
+}
+
+/// `name=lo:hi:points` で指定されたパラメータの範囲
+struct SweepRange {
+    id: usize,
+    lo: i64,
+    hi: i64,
+    points: usize,
+}
+
+fn exit(msg: String) -> ! {
+    eprintln!("{}", msg);
+    std::process::exit(1)
+}
+
+fn invalid_sweep(s: &str) -> ! {
+    exit(format!("invalid sweep: {} (expected name=lo:hi:points)", s))
+}
+
+fn parse_sweep(s: &str, params: &[Param]) -> SweepRange {
+    let Some((name, range)) = s.split_once('=') else {
+        invalid_sweep(s)
+    };
+    let Some(id) = params.iter().position(|p| p.name == name) else {
+        invalid_sweep(s)
+    };
+    let p = &params[id];
+    let vs = range.split(':').collect::<Vec<_>>();
+    if vs.len() != 3 {
+        invalid_sweep(s);
+    }
+    let (Some(lo), Some(hi), Ok(points)) = (p.parse(vs[0]), p.parse(vs[1]), vs[2].parse::<usize>())
+    else {
+        invalid_sweep(s)
+    };
+    if lo > hi || points == 0 {
+        invalid_sweep(s);
+    }
+    if lo < p.lo || hi > p.hi {
+        exit(format!(
+            "invalid sweep: {} ({} must be in {}..={})",
+            s,
+            name,
+            p.format(p.lo),
+            p.format(p.hi)
+        ));
+    }
+    SweepRange { id, lo, hi, points }
+}
+
+/// グリッド: 各範囲を等間隔に points 点取った直積
+/// 層化: 各範囲を points 個の層に分けたラテン超方格で、最初と最後の層では範囲の端の値を取る
+/// どちらも各点を repeat 回ずつ繰り返す
+fn sweep_points(ranges: &[SweepRange], stratified: bool, repeat: usize) -> Vec<Vec<i64>> {
+    let points = if stratified {
+        let n = ranges[0].points;
+        if ranges.iter().any(|r| r.points != n) {
+            exit(format!(
+                "stratified sweep needs the same number of points for every parameter"
+            ));
+        }
+        let mut rng = rand_chacha::ChaCha20Rng::seed_from_u64(0);
+        let mut points = vec![vec![]; n];
+        for r in ranges {
+            let mut strata = (0..n).collect::<Vec<_>>();
+            strata.shuffle(&mut rng);
+            let width = (r.hi - r.lo + 1) as f64;
+            for (p, s) in points.iter_mut().zip(strata) {
+                let v = if s == 0 {
+                    r.lo
+                } else if s == n - 1 {
+                    r.hi
+                } else {
+                    let x = (s as f64 + rng.gen::<f64>()) / n as f64;
+                    (r.lo + (width * x).floor() as i64).min(r.hi)
+                };
+                p.push(v);
+            }
+        }
+        points
+    } else {
+        let mut points = vec![vec![]];
+        for r in ranges {
+            let mut next = vec![];
+            for p in &points {
+                for k in 0..r.points {
+                    let v = if r.points == 1 {
+                        r.lo
+                    } else {
+                        r.lo + ((r.hi - r.lo) as f64 * k as f64 / (r.points - 1) as f64).round()
+                            as i64
+                    };
+                    let mut p = p.clone();
+                    p.push(v);
+                    next.push(p);
+                }
+            }
+            points = next;
+        }
+        points
+    };
+    points
+        .into_iter()
+        .flat_map(|p| std::iter::repeat(p).take(repeat))
+        .collect()
+}
+
+/// 各 seed で生成するパラメータを決める
+/// sweep がなければ全ての seed で fix を使い、あれば sweep の各点に先頭の seed から順に割り当てる
+pub fn jobs(
+    seeds: &[u64],
+    params: &[Param],
+    fix: Vec<Option<i64>>,
+    sweep: &[String],
+    stratified: bool,
+    repeat: usize,
+) -> Vec<(u64, Vec<Option<i64>>)> {
+    for (p, v) in params.iter().zip(fix.iter()) {
+        if let &Some(v) = v {
+            if v < p.lo || v > p.hi {
+                exit(format!(
+                    "invalid {}: {} (must be in {}..={})",
+                    p.name,
+                    v,
+                    p.format(p.lo),
+                    p.format(p.hi)
+                ));
+            }
+        }
+    }
+    if sweep.is_empty() {
+        if stratified || repeat != 1 {
+            exit(format!("--stratified and --repeat need --sweep"));
+        }
+        return seeds.iter().map(|&seed| (seed, fix.clone())).collect();
+    }
+    if repeat == 0 {
+        exit(format!("--repeat must be positive"));
+    }
+    let ranges = sweep
+        .iter()
+        .map(|s| parse_sweep(s, params))
+        .collect::<Vec<_>>();
+    let points = sweep_points(&ranges, stratified, repeat);
+    if points.len() > seeds.len() {
+        exit(format!(
+            "not enough seeds: {} < {}",
+            seeds.len(),
+            points.len()
+        ));
+    }
+    seeds
+        .iter()
+        .zip(points)
+        .map(|(&seed, point)| {
+            let mut fix = fix.clone();
+            for (r, v) in ranges.iter().zip(point) {
+                fix[r.id] = Some(v);
+            }
+            (seed, fix)
+        })
+        .collect()
+}